@@ -0,0 +1,174 @@
+use syn::meta::ParseNestedMeta;
+use syn::{
+    Attribute, DeriveInput, Error, ExprPath, Fields, LitStr, Member, Result, Token, Variant,
+};
+
+/// call `f` for every item inside the `#[json(...)]` attributes
+fn parse_json_attrs(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> Result<()>,
+) -> Result<()> {
+    for attr in attrs {
+        if attr.path().is_ident("json") {
+            attr.parse_nested_meta(&mut f)?;
+        }
+    }
+    Ok(())
+}
+
+fn string_value(meta: &ParseNestedMeta) -> Result<String> {
+    Ok(meta.value()?.parse::<LitStr>()?.value())
+}
+
+/// how the variant of an enum is written
+pub enum Tagging {
+    /// `"Unit"` or `{"Variant": content}`, the default
+    External,
+    /// `{"tag": "Variant", ..the members of the content}` or `{"tag": "Variant", "value": content}`
+    /// when the content isn't an object or its members would clash, from `#[json(tag = "..")]`
+    Internal { tag: String },
+    /// `{"tag": "Variant", "content": content}`, from `#[json(tag = "..", content = "..")]`
+    Adjacent { tag: String, content: String },
+    /// only the content, from `#[json(untagged)]`
+    Untagged,
+}
+
+impl Tagging {
+    pub fn from_input(input: &DeriveInput) -> Result<Self> {
+        let mut tag = None;
+        let mut content = None;
+        let mut untagged = false;
+        parse_json_attrs(&input.attrs, |meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(string_value(&meta)?);
+            } else if meta.path.is_ident("content") {
+                content = Some(string_value(&meta)?);
+            } else if meta.path.is_ident("untagged") {
+                untagged = true;
+            } else {
+                return Err(
+                    meta.error("unknown json attribute, expected `tag`, `content` or `untagged`")
+                );
+            }
+            Ok(())
+        })?;
+
+        let tagging = match (tag, content, untagged) {
+            (None, None, false) => Tagging::External,
+            (None, None, true) => Tagging::Untagged,
+            (Some(tag), None, false) => Tagging::Internal { tag },
+            (Some(tag), Some(content), false) => Tagging::Adjacent { tag, content },
+            (None, Some(_), _) => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "`content` needs a `tag` as well",
+                ))
+            }
+            (Some(_), _, true) => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "`untagged` can't be used together with `tag`",
+                ))
+            }
+        };
+        if !matches!(tagging, Tagging::External) && !matches!(input.data, syn::Data::Enum(_)) {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`tag`, `content` and `untagged` can only be used on enums",
+            ));
+        }
+        Ok(tagging)
+    }
+}
+
+/// where the value of a field comes from when it isn't in the JSON
+pub enum DefaultValue {
+    /// `#[json(default)]`, uses `Default::default`
+    Trait,
+    /// `#[json(default = "path")]`, calls the function
+    Function(ExprPath),
+}
+
+/// a field of a struct or variant with its `#[json(...)]` attributes
+pub struct Field {
+    pub member: Member,
+    /// the key in the JSON object, the field name unless renamed
+    pub key: String,
+    pub default: Option<DefaultValue>,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+impl Field {
+    pub fn from_fields(fields: &Fields) -> Result<Vec<Self>> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let member = match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(i.into()),
+                };
+                let mut parsed = Field {
+                    key: field
+                        .ident
+                        .as_ref()
+                        .map(|ident| ident.to_string().trim_start_matches("r#").to_string())
+                        .unwrap_or_default(),
+                    member,
+                    default: None,
+                    skip: false,
+                    flatten: false,
+                };
+                parse_json_attrs(&field.attrs, |meta| {
+                    if field.ident.is_none() {
+                        return Err(meta.error("json attributes can only be used on named fields"));
+                    }
+                    if meta.path.is_ident("rename") {
+                        parsed.key = string_value(&meta)?;
+                    } else if meta.path.is_ident("default") {
+                        parsed.default = Some(if meta.input.peek(Token![=]) {
+                            DefaultValue::Function(meta.value()?.parse::<LitStr>()?.parse()?)
+                        } else {
+                            DefaultValue::Trait
+                        });
+                    } else if meta.path.is_ident("skip") {
+                        parsed.skip = true;
+                    } else if meta.path.is_ident("flatten") {
+                        parsed.flatten = true;
+                    } else {
+                        return Err(meta.error(
+                            "unknown json attribute, expected `rename`, `default`, `skip` or `flatten`",
+                        ));
+                    }
+                    Ok(())
+                })?;
+                if parsed.flatten && (parsed.skip || parsed.default.is_some()) {
+                    return Err(Error::new_spanned(
+                        field,
+                        "`flatten` can't be used together with `skip` or `default`",
+                    ));
+                }
+                Ok(parsed)
+            })
+            .collect()
+    }
+}
+
+/// the key a variant is written with, the variant name unless renamed
+pub fn variant_key(variant: &Variant) -> Result<String> {
+    let mut key = variant
+        .ident
+        .to_string()
+        .trim_start_matches("r#")
+        .to_string();
+    parse_json_attrs(&variant.attrs, |meta| {
+        if meta.path.is_ident("rename") {
+            key = string_value(&meta)?;
+            Ok(())
+        } else {
+            Err(meta.error("unknown json attribute, expected `rename`"))
+        }
+    })?;
+    Ok(key)
+}
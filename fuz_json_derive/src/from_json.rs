@@ -0,0 +1,210 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Result};
+
+use crate::attr::{variant_key, DefaultValue, Field, Tagging};
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let tagging = Tagging::from_input(input)?;
+    let name = &input.ident;
+    let expected = name.to_string();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = Field::from_fields(&data.fields)?;
+            content(&data.fields, &fields, quote!(Self), &expected, true)
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let fields = Field::from_fields(&variant.fields)?;
+                if matches!(tagging, Tagging::Internal { .. })
+                    && matches!(&variant.fields, Fields::Unnamed(f) if f.unnamed.len() > 1)
+                {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "an internally tagged enum can't have tuple variants",
+                    ));
+                }
+                let read = match (&tagging, &variant.fields) {
+                    (Tagging::Internal { tag }, Fields::Unnamed(_)) => quote! {
+                        ::std::result::Result::Ok(Self::#ident(__fuz::internal_content(__value, #tag)?))
+                    },
+                    _ => content(
+                        &variant.fields,
+                        &fields,
+                        quote!(Self::#ident),
+                        &format!("{name}::{ident}"),
+                        matches!(tagging, Tagging::Untagged),
+                    ),
+                };
+                variants.push((variant_key(variant)?, read));
+            }
+            enum_body(&tagging, &expected, &variants)
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FromJson can't be derived for a union",
+            ))
+        }
+    };
+
+    let generics = crate::with_bound(&input.generics, parse_quote!(__fuz::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        const _: () = {
+            use ::fuz_json_parser::__private as __fuz;
+
+            impl #impl_generics __fuz::FromJson for #name #ty_generics #where_clause {
+                fn from_json(
+                    __value: &__fuz::JsonValue,
+                ) -> ::std::result::Result<Self, __fuz::ConversionError> {
+                    #body
+                }
+            }
+        };
+    })
+}
+
+/// wrap `read` so `?` inside it stops at the closure, the result is still a `Result`
+fn catch(read: &TokenStream) -> TokenStream {
+    quote! {
+        (|| -> ::std::result::Result<Self, __fuz::ConversionError> { #read })()
+    }
+}
+
+/// read the variants of an enum, each one is the key it is written with and the code that
+/// reads its content from `__value`
+fn enum_body(tagging: &Tagging, expected: &str, variants: &[(String, TokenStream)]) -> TokenStream {
+    match tagging {
+        Tagging::External => {
+            let arms = variants.iter().map(|(key, read)| {
+                let read = catch(read);
+                quote! {
+                    #key => {
+                        let __value = __content;
+                        #read.map_err(|__error| __error.at_key(#key))
+                    }
+                }
+            });
+            quote! {
+                let (__tag, __content) = __fuz::external(__value, #expected)?;
+                match __tag {
+                    #(#arms)*
+                    _ => ::std::result::Result::Err(__fuz::unknown_variant(#expected, __tag)),
+                }
+            }
+        }
+        Tagging::Internal { tag } => {
+            let arms = variants
+                .iter()
+                .map(|(key, read)| quote!(#key => { #read }))
+                .collect();
+            tag_match(tag, expected, arms)
+        }
+        Tagging::Adjacent { tag, content } => {
+            let arms = variants
+                .iter()
+                .map(|(key, read)| {
+                    let read = catch(read);
+                    quote! {
+                        #key => {
+                            let __value = __content;
+                            #read.map_err(|__error| __error.at_key(#content))
+                        }
+                    }
+                })
+                .collect();
+            let body = tag_match(tag, expected, arms);
+            quote! {
+                let __content = __fuz::object(__value, #expected)?
+                    .get(#content)
+                    .unwrap_or(&__fuz::JsonValue::Null);
+                #body
+            }
+        }
+        Tagging::Untagged => {
+            let attempts = variants.iter().map(|(_, read)| {
+                let read = catch(read);
+                quote! {
+                    if let ::std::result::Result::Ok(__variant) = #read {
+                        return ::std::result::Result::Ok(__variant);
+                    }
+                }
+            });
+            quote! {
+                #(#attempts)*
+                ::std::result::Result::Err(__fuz::mismatch(#expected, __value))
+            }
+        }
+    }
+}
+
+/// match the variant name in the member `tag` against `arms`
+fn tag_match(tag: &str, expected: &str, arms: Vec<TokenStream>) -> TokenStream {
+    quote! {
+        match __fuz::tag(__fuz::object(__value, #expected)?, #tag, #expected)? {
+            #(#arms)*
+            __tag => ::std::result::Result::Err(
+                __fuz::unknown_variant(#expected, __tag).at_key(#tag),
+            ),
+        }
+    }
+}
+
+/// the code that reads the fields of a struct or variant out of `__value` and builds it
+/// with `constructor`
+///
+/// no fields at all is written as `null`, but variants without fields accept anything as
+/// their content unless `null_only` is set
+fn content(
+    shape: &Fields,
+    fields: &[Field],
+    constructor: TokenStream,
+    expected: &str,
+    null_only: bool,
+) -> TokenStream {
+    match shape {
+        Fields::Named(_) => {
+            let members = fields.iter().map(|field| {
+                let member = &field.member;
+                let key = &field.key;
+                let default = match &field.default {
+                    Some(DefaultValue::Function(path)) => quote!(#path),
+                    _ => quote!(::std::default::Default::default),
+                };
+                let value = if field.skip {
+                    quote!(#default())
+                } else if field.flatten {
+                    quote!(__fuz::FromJson::from_json(__value)?)
+                } else if field.default.is_some() {
+                    quote!(__fuz::field_or(__object, #key, #default)?)
+                } else {
+                    quote!(__fuz::field(__object, #key)?)
+                };
+                quote!(#member: #value)
+            });
+            quote! {
+                let __object = __fuz::object(__value, #expected)?;
+                ::std::result::Result::Ok(#constructor { #(#members),* })
+            }
+        }
+        Fields::Unnamed(_) if fields.len() == 1 => {
+            quote!(::std::result::Result::Ok(#constructor(__fuz::FromJson::from_json(__value)?)))
+        }
+        Fields::Unnamed(_) => {
+            let len = fields.len();
+            let elements = (0..len).map(|i| quote!(__fuz::element(__array, #i)?));
+            quote! {
+                let __array = __fuz::array(__value, #expected, #len)?;
+                ::std::result::Result::Ok(#constructor(#(#elements),*))
+            }
+        }
+        Fields::Unit if null_only => quote! {
+            __fuz::null(__value, #expected)?;
+            ::std::result::Result::Ok(#constructor)
+        },
+        Fields::Unit => quote!(::std::result::Result::Ok(#constructor)),
+    }
+}
@@ -0,0 +1,53 @@
+//! `#[derive(ToJson, FromJson)]` for the traits of `fuz_json_parser`
+//!
+//! use it through the `derive` feature of `fuz_json_parser`, which re-exports both macros.
+//! Structs with named fields are objects, tuple structs are arrays (or the inner value for
+//! a single field) and unit structs are `null`. Enums are written as `"Variant"` or
+//! `{"Variant": content}` unless a tagging attribute says otherwise
+//!
+//! attributes on the struct or enum:
+//! - `#[json(tag = "type")]` writes the variant name as the `type` member of the content,
+//!   content that isn't an object, or whose members would clash with the tag, goes in a
+//!   `value` member next to it
+//! - `#[json(tag = "t", content = "c")]` writes `{"t": "Variant", "c": content}`
+//! - `#[json(untagged)]` writes only the content, reading tries each variant in order
+//!
+//! attributes on named fields:
+//! - `#[json(rename = "name")]` uses `name` as the key
+//! - `#[json(default)]` or `#[json(default = "path::to::function")]` for a missing member
+//! - `#[json(skip)]` leaves the field out, reading it from its default
+//! - `#[json(flatten)]` writes the members of the field into the outer object
+//!
+//! and `#[json(rename = "name")]` on enum variants
+
+mod attr;
+mod from_json;
+mod to_json;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, Generics, TypeParamBound};
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_json::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// `generics` with `bound` added to every type parameter
+fn with_bound(generics: &Generics, bound: TypeParamBound) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(bound.clone());
+    }
+    generics
+}
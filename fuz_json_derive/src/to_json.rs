@@ -0,0 +1,148 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Error, Fields, Result};
+
+use crate::attr::{variant_key, Field, Tagging};
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let tagging = Tagging::from_input(input)?;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = Field::from_fields(&data.fields)?;
+            content(&data.fields, &fields, |_, field| {
+                let member = &field.member;
+                quote!(&self.#member)
+            })
+            .unwrap_or_else(|| quote!(__fuz::JsonValue::Null))
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let key = variant_key(variant)?;
+                    let fields = Field::from_fields(&variant.fields)?;
+                    let bindings = fields.iter().enumerate().filter(|(_, field)| !field.skip).map(
+                        |(i, field)| {
+                            let member = &field.member;
+                            let binding = format_ident!("__field{}", i);
+                            quote!(#member: #binding)
+                        },
+                    );
+                    let content = content(&variant.fields, &fields, |i, _| {
+                        let binding = format_ident!("__field{}", i);
+                        quote!(#binding)
+                    });
+                    let value = match (&tagging, content) {
+                        (Tagging::External, None) => quote!(__fuz::JsonValue::from(#key)),
+                        (Tagging::External, Some(content)) => quote!(__fuz::single(#key, #content)),
+                        (Tagging::Internal { tag }, None) => {
+                            quote!(__fuz::single(#tag, __fuz::JsonValue::from(#key)))
+                        }
+                        (Tagging::Internal { .. }, Some(_))
+                            if matches!(&variant.fields, Fields::Unnamed(f) if f.unnamed.len() > 1) =>
+                        {
+                            return Err(Error::new_spanned(
+                                variant,
+                                "an internally tagged enum can't have tuple variants",
+                            ))
+                        }
+                        (Tagging::Internal { tag }, Some(content))
+                            if matches!(&variant.fields, Fields::Unnamed(_)) =>
+                        {
+                            quote!(__fuz::tag_content(#tag, #key, #content))
+                        }
+                        (Tagging::Internal { tag }, Some(content)) => {
+                            quote!(__fuz::tag_object(#tag, #key, #content))
+                        }
+                        (Tagging::Adjacent { tag, .. }, None) => {
+                            quote!(__fuz::single(#tag, __fuz::JsonValue::from(#key)))
+                        }
+                        (Tagging::Adjacent { tag, content: content_key }, Some(content)) => {
+                            quote!(__fuz::adjacent(#tag, #key, #content_key, #content))
+                        }
+                        (Tagging::Untagged, None) => quote!(__fuz::JsonValue::Null),
+                        (Tagging::Untagged, Some(content)) => content,
+                    };
+                    Ok(quote!(Self::#ident { #(#bindings,)* .. } => #value,))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "ToJson can't be derived for a union",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let generics = crate::with_bound(&input.generics, parse_quote!(__fuz::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        const _: () = {
+            use ::fuz_json_parser::__private as __fuz;
+
+            impl #impl_generics __fuz::ToJson for #name #ty_generics #where_clause {
+                fn to_json(&self) -> __fuz::JsonValue {
+                    #body
+                }
+            }
+        };
+    })
+}
+
+/// the JSON of the fields of a struct or variant, `None` when there are no fields at all
+///
+/// `access` gives a reference to the value of the field at an index
+fn content(
+    shape: &Fields,
+    fields: &[Field],
+    access: impl Fn(usize, &Field) -> TokenStream,
+) -> Option<TokenStream> {
+    match shape {
+        Fields::Named(_) => {
+            let members = fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| !field.skip)
+                .map(|(i, field)| {
+                    let value = access(i, field);
+                    let key = &field.key;
+                    if field.flatten {
+                        quote!(__fuz::flatten_into(&mut __object, __fuz::ToJson::to_json(#value));)
+                    } else {
+                        quote! {
+                            __object.insert(
+                                ::std::string::String::from(#key),
+                                __fuz::ToJson::to_json(#value),
+                            );
+                        }
+                    }
+                });
+            Some(quote! {{
+                let mut __object = __fuz::Object::new();
+                #(#members)*
+                __fuz::JsonValue::Obj(__object)
+            }})
+        }
+        Fields::Unnamed(_) if fields.len() == 1 => {
+            let value = access(0, &fields[0]);
+            Some(quote!(__fuz::ToJson::to_json(#value)))
+        }
+        Fields::Unnamed(_) => {
+            let elements = fields.iter().enumerate().map(|(i, field)| {
+                let value = access(i, field);
+                quote!(__fuz::ToJson::to_json(#value))
+            });
+            Some(quote!(__fuz::JsonValue::Array(::std::vec![#(#elements),*])))
+        }
+        Fields::Unit => None,
+    }
+}
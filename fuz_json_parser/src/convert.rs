@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::error::ConversionError;
+use crate::value_ref::JsonValueRef;
+use crate::values::{JsonNum, JsonValue};
+
+/// `true` and `false` become `JsonValue::Bool`
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+macro_rules! from_integer {
+    ($($int:ty),*) => {
+        $(
+            /// the integer becomes `JsonValue::Num(JsonNum::Int)`
+            impl From<$int> for JsonValue {
+                fn from(value: $int) -> Self {
+                    JsonValue::Num(JsonNum::Int(value as i128))
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+/// the integer becomes `JsonValue::Num(JsonNum::Int)`, or
+/// `JsonValue::Num(JsonNum::UInt)` when it is above `i128::MAX`
+impl From<u128> for JsonValue {
+    fn from(value: u128) -> Self {
+        match i128::try_from(value) {
+            Ok(int) => JsonValue::Num(JsonNum::Int(int)),
+            Err(_) => JsonValue::Num(JsonNum::UInt(value)),
+        }
+    }
+}
+
+/// the float becomes `JsonValue::Num(JsonNum::Float)`
+impl From<f32> for JsonValue {
+    fn from(value: f32) -> Self {
+        JsonValue::Num(JsonNum::Float(value as f64))
+    }
+}
+
+/// the float becomes `JsonValue::Num(JsonNum::Float)`
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Num(JsonNum::Float(value))
+    }
+}
+
+/// the number becomes `JsonValue::Num`
+impl From<JsonNum> for JsonValue {
+    fn from(value: JsonNum) -> Self {
+        JsonValue::Num(value)
+    }
+}
+
+/// the text is copied into a `JsonValue::String`
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+/// the text becomes a `JsonValue::String`
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+/// the text is copied into a `JsonValue::String`
+impl From<&String> for JsonValue {
+    fn from(value: &String) -> Self {
+        JsonValue::String(value.clone())
+    }
+}
+
+/// the character becomes a one character `JsonValue::String`
+impl From<char> for JsonValue {
+    fn from(value: char) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+/// `None` becomes `JsonValue::Null`
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+/// the elements are converted into a `JsonValue::Array`
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(value: Vec<T>) -> Self {
+        JsonValue::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// the elements are copied into a `JsonValue::Array`
+impl<T: Clone + Into<JsonValue>> From<&[T]> for JsonValue {
+    fn from(value: &[T]) -> Self {
+        JsonValue::Array(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+/// the members are converted into a `JsonValue::Obj`
+impl<K: Into<String>, T: Into<JsonValue>, S> From<HashMap<K, T, S>> for JsonValue {
+    fn from(value: HashMap<K, T, S>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// the members are converted into a `JsonValue::Obj`
+impl<K: Into<String>, T: Into<JsonValue>> From<BTreeMap<K, T>> for JsonValue {
+    fn from(value: BTreeMap<K, T>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// the members are converted into a `JsonValue::Obj`
+#[cfg(feature = "preserve_order")]
+impl<K: Into<String>, T: Into<JsonValue>, S> From<indexmap::IndexMap<K, T, S>> for JsonValue {
+    fn from(value: indexmap::IndexMap<K, T, S>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// same as [JsonValueRef::into_owned]
+impl From<JsonValueRef<'_>> for JsonValue {
+    fn from(value: JsonValueRef<'_>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// collect values into a `JsonValue::Array`
+impl<T: Into<JsonValue>> FromIterator<T> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JsonValue::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// collect key value pairs into a `JsonValue::Obj`, a repeated key keeps the last value
+impl<K: Into<String>, T: Into<JsonValue>> FromIterator<(K, T)> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(iter: I) -> Self {
+        JsonValue::Obj(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// the error for `value` not being convertible to `expected`
+pub(crate) fn mismatch(expected: &str, value: &JsonValue) -> ConversionError {
+    match value {
+        JsonValue::Num(num) => ConversionError::new(expected, format!("the number {num}")),
+        _ => ConversionError::new(expected, value.type_name()),
+    }
+}
+
+/// only `JsonValue::Bool` converts to `bool`
+impl TryFrom<&JsonValue> for bool {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or_else(|| mismatch("a bool", value))
+    }
+}
+
+macro_rules! try_from_integer {
+    ($($int:ty => $wide:ident),*) => {
+        $(
+            /// integers in range convert, floats and integers out of range are errors
+            impl TryFrom<&JsonValue> for $int {
+                type Error = ConversionError;
+
+                fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+                    value
+                        .as_number()
+                        .and_then(JsonNum::$wide)
+                        .and_then(|wide| wide.try_into().ok())
+                        .ok_or_else(|| mismatch(stringify!($int), value))
+                }
+            }
+        )*
+    };
+}
+
+try_from_integer!(
+    i8 => as_i128, i16 => as_i128, i32 => as_i128, i64 => as_i128, i128 => as_i128,
+    isize => as_i128, u8 => as_u128, u16 => as_u128, u32 => as_u128, u64 => as_u128,
+    u128 => as_u128, usize => as_u128
+);
+
+/// any number converts, integers are rounded to the nearest `f64`
+impl TryFrom<&JsonValue> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or_else(|| mismatch("f64", value))
+    }
+}
+
+/// any number in the range of `f32` converts, rounded to the nearest `f32`
+impl TryFrom<&JsonValue> for f32 {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value
+            .as_f64()
+            .map(|float| float as f32)
+            .filter(|float| float.is_finite())
+            .ok_or_else(|| mismatch("f32", value))
+    }
+}
+
+/// only `JsonValue::String` converts to `String`
+impl TryFrom<&JsonValue> for String {
+    type Error = ConversionError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| mismatch("a string", value))
+    }
+}
+
+/// only `JsonValue::String` converts to `&str`, borrowing its text
+impl<'a> TryFrom<&'a JsonValue> for &'a str {
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        value.as_str().ok_or_else(|| mismatch("a string", value))
+    }
+}
+
+/// `JsonValue::Null` converts to `None`, anything else has to convert to `T`
+impl<'a, T: TryFrom<&'a JsonValue, Error = ConversionError>> TryFrom<&'a JsonValue> for Option<T> {
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Null => Ok(None),
+            _ => T::try_from(value).map(Some),
+        }
+    }
+}
+
+/// convert every element of a `JsonValue::Array` with `convert`, errors get the index added
+///
+/// shared by the `TryFrom` impls here and the [FromJson](crate::traits::FromJson) impls
+pub(crate) fn collect_array<'a, T, C>(
+    value: &'a JsonValue,
+    convert: impl Fn(&'a JsonValue) -> Result<T, ConversionError>,
+) -> Result<C, ConversionError>
+where
+    C: FromIterator<T>,
+{
+    value
+        .as_array()
+        .ok_or_else(|| mismatch("an array", value))?
+        .iter()
+        .enumerate()
+        .map(|(i, element)| convert(element).map_err(|e| e.at_index(i)))
+        .collect()
+}
+
+/// convert every member of a `JsonValue::Obj` with `convert` into a map of type `M`, errors
+/// get the key added
+pub(crate) fn collect_object<'a, T, M>(
+    value: &'a JsonValue,
+    convert: impl Fn(&'a JsonValue) -> Result<T, ConversionError>,
+) -> Result<M, ConversionError>
+where
+    M: FromIterator<(String, T)>,
+{
+    value
+        .as_object()
+        .ok_or_else(|| mismatch("an object", value))?
+        .iter()
+        .map(|(key, member)| Ok((key.clone(), convert(member).map_err(|e| e.at_key(key))?)))
+        .collect()
+}
+
+/// a `JsonValue::Array` where every element converts to `T`
+impl<'a, T: TryFrom<&'a JsonValue, Error = ConversionError>> TryFrom<&'a JsonValue> for Vec<T> {
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        collect_array(value, T::try_from)
+    }
+}
+
+/// a `JsonValue::Obj` where every member converts to `T`
+impl<'a, T, S> TryFrom<&'a JsonValue> for HashMap<String, T, S>
+where
+    T: TryFrom<&'a JsonValue, Error = ConversionError>,
+    S: BuildHasher + Default,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        collect_object(value, T::try_from)
+    }
+}
+
+/// a `JsonValue::Obj` where every member converts to `T`
+impl<'a, T> TryFrom<&'a JsonValue> for BTreeMap<String, T>
+where
+    T: TryFrom<&'a JsonValue, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        collect_object(value, T::try_from)
+    }
+}
+
+/// a `JsonValue::Obj` where every member converts to `T`
+#[cfg(feature = "preserve_order")]
+impl<'a, T, S> TryFrom<&'a JsonValue> for indexmap::IndexMap<String, T, S>
+where
+    T: TryFrom<&'a JsonValue, Error = ConversionError>,
+    S: BuildHasher + Default,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &'a JsonValue) -> Result<Self, Self::Error> {
+        collect_object(value, T::try_from)
+    }
+}
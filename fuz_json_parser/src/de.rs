@@ -0,0 +1,369 @@
+//! a serde [Deserializer](serde::Deserializer) that reads straight from the source text
+//!
+//! values are handed to the `Deserialize` implementation as they are parsed, no [JsonValue]
+//! tree is built in between. Strings without escapes are borrowed from the source, so types
+//! with `&str` fields can be deserialized without copying
+//!
+//! [JsonValue]: crate::values::JsonValue
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use serde::de::value::{BorrowedStrDeserializer, StringDeserializer, UnitDeserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::create_state;
+use crate::error::{ErrorKind, FuzJsonParseError, Result};
+use crate::json_parser::options::DuplicateKeys;
+use crate::json_parser::parsers::{main_parse, parse_string};
+use crate::json_parser::state::{
+    advance, assert_char, check_char, consume_whitespace, peek, unexpected, ParserState,
+};
+use crate::json_parser::{check_trailing, ParseOptions};
+use crate::value_ref::JsonValueRef;
+use crate::value_serde::visit_number;
+
+/// deserialize a `T` from a json string using the default [ParseOptions]
+///
+/// see [from_str_with_options]
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Package<'a> {
+///     name: &'a str,
+///     versions: Vec<u32>,
+/// }
+///
+/// let package: Package = fuz_json_parser::from_str(r#"{"name": "fuz", "versions": [1, 2]}"#).unwrap();
+/// assert_eq!(package, Package { name: "fuz", versions: vec![1, 2] });
+/// ```
+pub fn from_str<'de, T: de::Deserialize<'de>>(json_str: &'de str) -> Result<T> {
+    from_str_with_options(json_str, &ParseOptions::default())
+}
+
+/// deserialize a `T` from a json string, `options` work the same as for
+/// [parse_with_options](crate::json_parser::parse_with_options)
+///
+/// [ParseOptions::duplicate_keys] is only checked for [DuplicateKeys::Error], with the other
+/// policies every member is passed on and the `Deserialize` implementation decides. Derived
+/// structs fail on a repeated field and maps keep the last value
+///
+/// numbers are passed on as the smallest of `i64`, `u64`, `i128` and `u128` they fit in or as an
+/// `f64`, [ParseOptions::arbitrary_precision] doesn't keep more digits than those types hold
+pub fn from_str_with_options<'de, T: de::Deserialize<'de>>(
+    json_str: &'de str,
+    options: &ParseOptions,
+) -> Result<T> {
+    let mut deserializer = Deserializer::new(json_str, *options);
+    deserializer.state.check_input_len()?;
+    consume_whitespace(&mut deserializer.state);
+    let start = deserializer.state.position();
+    let value = match peek(&deserializer.state) {
+        Some(_) => T::deserialize(&mut deserializer),
+        None if options.allow_empty_input => T::deserialize(UnitDeserializer::new()),
+        None => {
+            return Err(deserializer
+                .state
+                .error(ErrorKind::EmptyInput, "input is empty"))
+        }
+    };
+    // `try_from` and `from` conversions fail after the value was read
+    let value = value.map_err(|e| deserializer.fix_position(e, start))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// reads values from a json string for `Deserialize` implementations
+///
+/// [from_str] and [from_str_with_options] cover reading a whole document, use this directly
+/// to read several values one after another and call [end](Deserializer::end) after the last
+pub struct Deserializer<'de> {
+    state: ParserState<'de>,
+}
+
+impl<'de> Deserializer<'de> {
+    /// create a deserializer with the cursor at the start of `json_str`
+    pub fn new(json_str: &'de str, options: ParseOptions) -> Self {
+        Deserializer {
+            state: create_state!(json_str, options),
+        }
+    }
+
+    /// make sure there is nothing but whitespace left after the values that were read
+    pub fn end(&mut self) -> Result<()> {
+        check_trailing(&mut self.state)
+    }
+
+    /// give an error from the `Deserialize` implementation the position of the value at `start`
+    fn fix_position(&self, error: FuzJsonParseError, start: usize) -> FuzJsonParseError {
+        if error.has_position() {
+            error
+        } else {
+            self.state.error_at(start, error.kind(), error.message())
+        }
+    }
+
+    /// count and enter the array or object that starts at the cursor
+    fn enter_nested(&mut self) -> Result<()> {
+        self.state.count_node()?;
+        let start = self.state.position();
+        self.state.enter_nested(start)?;
+        advance(&mut self.state);
+        consume_whitespace(&mut self.state);
+        Ok(())
+    }
+
+    /// consume the `close` of the array or object and leave it
+    fn leave_nested(&mut self, close: u8) -> Result<()> {
+        consume_whitespace(&mut self.state);
+        if !check_char(&mut self.state, close) {
+            return Err(unexpected(&self.state, &format!("`{}`", close as char)));
+        }
+        self.state.leave_nested();
+        Ok(())
+    }
+
+    /// parse the key of an object member and the `:` after it
+    fn parse_key(&mut self) -> Result<Cow<'de, str>> {
+        let key = parse_string(&mut self.state)?;
+        consume_whitespace(&mut self.state);
+        assert_char(&mut self.state, b':', false)?;
+        consume_whitespace(&mut self.state);
+        Ok(key)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = FuzJsonParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        consume_whitespace(&mut self.state);
+        let start = self.state.position();
+        let value = match peek(&self.state) {
+            Some(b'[') => {
+                self.enter_nested()?;
+                let value = visitor.visit_seq(SeqAccess {
+                    de: self,
+                    first: true,
+                    len: 0,
+                });
+                value.and_then(|value| self.leave_nested(b']').map(|_| value))
+            }
+            Some(b'{') => {
+                self.enter_nested()?;
+                let value = visitor.visit_map(MapAccess {
+                    de: self,
+                    first: true,
+                    len: 0,
+                    keys: HashSet::new(),
+                });
+                value.and_then(|value| self.leave_nested(b'}').map(|_| value))
+            }
+            _ => match main_parse::<JsonValueRef>(&mut self.state)? {
+                JsonValueRef::Null => visitor.visit_unit(),
+                JsonValueRef::Bool(b_val) => visitor.visit_bool(b_val),
+                JsonValueRef::Num(num) => visit_number(num, visitor),
+                JsonValueRef::String(Cow::Borrowed(s_val)) => visitor.visit_borrowed_str(s_val),
+                JsonValueRef::String(Cow::Owned(s_val)) => visitor.visit_string(s_val),
+                // main_parse only gets here for scalars
+                JsonValueRef::Array(_) | JsonValueRef::Obj(_) => unreachable!(),
+            },
+        };
+        value.map_err(|e| self.fix_position(e, start))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        consume_whitespace(&mut self.state);
+        match peek(&self.state) {
+            Some(b'n' | b'N') => {
+                let start = self.state.position();
+                main_parse::<JsonValueRef>(&mut self.state)?;
+                visitor
+                    .visit_none()
+                    .map_err(|e| self.fix_position(e, start))
+            }
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// a unit variant is a string with its name, any other variant is an object with the
+    /// name as its only key and the content as the value
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        consume_whitespace(&mut self.state);
+        let start = self.state.position();
+        let value = match peek(&self.state) {
+            Some(b'"') => match parse_string(&mut self.state)? {
+                Cow::Borrowed(name) => visitor.visit_enum(name.into_deserializer()),
+                Cow::Owned(name) => visitor.visit_enum(name.into_deserializer()),
+            },
+            Some(b'{') => {
+                self.enter_nested()?;
+                let value = visitor.visit_enum(EnumAccess { de: self });
+                value.and_then(|value| self.leave_nested(b'}').map(|_| value))
+            }
+            _ => return Err(unexpected(&self.state, "`\"` or `{` to start an enum")),
+        };
+        value.map_err(|e| self.fix_position(e, start))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// the elements of an array, the cursor is after the `[` or the previous element
+struct SeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    first: bool,
+    len: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = FuzJsonParseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        let state = &mut self.de.state;
+        consume_whitespace(state);
+        if peek(state) == Some(b']') {
+            return Ok(None);
+        }
+        if !std::mem::take(&mut self.first) {
+            if !check_char(state, b',') {
+                return Err(unexpected(state, "either `,` or `]`"));
+            }
+            consume_whitespace(state);
+        }
+        let max_len = state.options.max_array_len.unwrap_or(usize::MAX);
+        if self.len == max_len {
+            return Err(state.error(
+                ErrorKind::ArrayTooLong,
+                format!("array has more elements than the limit of {max_len}"),
+            ));
+        }
+        self.len += 1;
+        let start = state.position();
+        seed.deserialize(&mut *self.de)
+            .map(Some)
+            .map_err(|e| self.de.fix_position(e, start))
+    }
+}
+
+/// the members of an object, the cursor is after the `{` or the previous member
+struct MapAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    first: bool,
+    len: usize,
+    /// keys seen so far, only kept for [DuplicateKeys::Error]
+    keys: HashSet<Cow<'de, str>>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_, 'de> {
+    type Error = FuzJsonParseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let state = &mut self.de.state;
+        consume_whitespace(state);
+        if peek(state) == Some(b'}') {
+            return Ok(None);
+        }
+        if !std::mem::take(&mut self.first) {
+            if !check_char(state, b',') {
+                return Err(unexpected(state, "either `,` or `}`"));
+            }
+            consume_whitespace(state);
+        }
+        let max_members = state.options.max_object_members.unwrap_or(usize::MAX);
+        if self.len == max_members {
+            return Err(state.error(
+                ErrorKind::TooManyMembers,
+                format!("object has more members than the limit of {max_members}"),
+            ));
+        }
+        self.len += 1;
+
+        let key_start = self.de.state.position();
+        let key = self.de.parse_key()?;
+        let state = &self.de.state;
+        if state.options.duplicate_keys == DuplicateKeys::Error && !self.keys.insert(key.clone()) {
+            return Err(state.error_at(
+                key_start,
+                ErrorKind::DuplicateKey,
+                format!("duplicate key `{}` in object", key.escape_debug()),
+            ));
+        }
+        let value = match key {
+            Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Cow::Owned(key) => seed.deserialize(StringDeserializer::new(key)),
+        };
+        value
+            .map(Some)
+            .map_err(|e| self.de.fix_position(e, key_start))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let start = self.de.state.position();
+        seed.deserialize(&mut *self.de)
+            .map_err(|e| self.de.fix_position(e, start))
+    }
+}
+
+/// the variant of an enum written as an object, the cursor is after the `{`
+struct EnumAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'_, 'de> {
+    type Error = FuzJsonParseError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let key_start = self.de.state.position();
+        let variant = match self.de.parse_key()? {
+            Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Cow::Owned(key) => seed.deserialize(StringDeserializer::new(key)),
+        };
+        let variant = variant.map_err(|e| self.de.fix_position(e, key_start))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumAccess<'_, 'de> {
+    type Error = FuzJsonParseError;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::error::{ErrorKind, FuzJsonParseError};
+use std::fmt::Write;
+
+/// colors used while rendering, every field is empty for plain text
+struct Style {
+    error: &'static str,
+    gutter: &'static str,
+    caret: &'static str,
+    note: &'static str,
+    reset: &'static str,
+}
+
+const PLAIN: Style = Style {
+    error: "",
+    gutter: "",
+    caret: "",
+    note: "",
+    reset: "",
+};
+
+const ANSI: Style = Style {
+    error: "\x1b[1;31m",
+    gutter: "\x1b[1;34m",
+    caret: "\x1b[1;31m",
+    note: "\x1b[1;36m",
+    reset: "\x1b[0m",
+};
+
+impl FuzJsonParseError {
+    /// render the error as plain text with the offending line of `source` and a caret under the column
+    ///
+    /// `source` must be the string that was parsed when the error was created
+    /// ```
+    /// use fuz_json_parser::json_parse;
+    ///
+    /// let source = "{\n  \"a\": 1,\n}";
+    /// let error = json_parse(source).unwrap_err();
+    /// assert_eq!(
+    ///     error.render(source),
+    ///     "error: expected `\"` found `}`
+    ///  --> line 3, column 1
+    ///   |
+    /// 3 | }
+    ///   | ^
+    ///   = note: a character appeared where it is not allowed
+    ///   = hint: JSON does not allow a trailing comma, remove the `,` before `}`
+    /// "
+    /// );
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        render_with(self, source, &PLAIN)
+    }
+
+    /// same as [render](FuzJsonParseError::render) but colored with ANSI escape codes for terminals
+    pub fn render_ansi(&self, source: &str) -> String {
+        render_with(self, source, &ANSI)
+    }
+}
+
+fn render_with(error: &FuzJsonParseError, source: &str, style: &Style) -> String {
+    let Style {
+        error: error_color,
+        gutter,
+        caret,
+        note,
+        reset,
+    } = style;
+    let mut rendered = String::new();
+    // writing to a `String` can't fail
+    let _ = writeln!(rendered, "{error_color}error{reset}: {}", error.message());
+    // an error from a `Deserialize` implementation that never reached the deserializer
+    // has no position to point at
+    if !error.has_position() {
+        let _ = writeln!(
+            rendered,
+            "  {gutter}={reset} {note}note{reset}: {}",
+            explanation(error.kind())
+        );
+        return rendered;
+    }
+
+    let line_text = source
+        .split('\n')
+        .nth(error.line() - 1)
+        .unwrap_or("")
+        .trim_end_matches('\r');
+    let line_number = error.line().to_string();
+    let gutter_pad = " ".repeat(line_number.len());
+    // keep tabs so the caret lines up with the source line
+    let caret_pad: String = line_text
+        .chars()
+        .take(error.column().saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    let _ = writeln!(
+        rendered,
+        "{gutter_pad}{gutter}-->{reset} line {}, column {}",
+        error.line(),
+        error.column()
+    );
+    let _ = writeln!(rendered, "{gutter_pad} {gutter}|{reset}");
+    let _ = writeln!(rendered, "{gutter}{line_number} |{reset} {line_text}");
+    let _ = writeln!(
+        rendered,
+        "{gutter_pad} {gutter}|{reset} {caret_pad}{caret}^{reset}"
+    );
+    let _ = writeln!(
+        rendered,
+        "{gutter_pad} {gutter}={reset} {note}note{reset}: {}",
+        explanation(error.kind())
+    );
+    if let Some(hint) = hint(error, source) {
+        let _ = writeln!(
+            rendered,
+            "{gutter_pad} {gutter}={reset} {note}hint{reset}: {hint}"
+        );
+    }
+    rendered
+}
+
+/// short explanation of what each [ErrorKind] means
+fn explanation(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::EmptyInput => "there is no JSON value to parse",
+        ErrorKind::UnexpectedEof => "the input ended before the JSON value was complete",
+        ErrorKind::UnexpectedChar => "a character appeared where it is not allowed",
+        ErrorKind::InvalidEscape => {
+            "valid escapes are \\\" \\\\ \\/ \\b \\f \\n \\r \\t and \\u followed by 4 hex digits"
+        }
+        ErrorKind::InvalidNumber => {
+            "numbers are an optional `-`, digits without leading zeros, an optional fraction and exponent"
+        }
+        ErrorKind::ControlCharacter => {
+            "characters below U+0020 must be escaped inside strings"
+        }
+        ErrorKind::TrailingCharacters => "a JSON document holds exactly one top level value",
+        ErrorKind::DuplicateKey => "each key can only appear once in an object",
+        ErrorKind::DepthExceeded => "arrays and objects are nested deeper than the parser allows",
+        ErrorKind::InputTooLarge => "the input is larger than the parser allows",
+        ErrorKind::StringTooLong => "a string is longer than the parser allows",
+        ErrorKind::ArrayTooLong => "an array has more elements than the parser allows",
+        ErrorKind::TooManyMembers => "an object has more members than the parser allows",
+        ErrorKind::TooManyNodes => "the document has more values than the parser allows",
+        ErrorKind::Custom => "the value doesn't match the type it is read into",
+    }
+}
+
+/// suggestion for common mistakes worked out from the source around the error
+fn hint(error: &FuzJsonParseError, source: &str) -> Option<String> {
+    // `source` isn't the parsed string when the offset doesn't fit in it
+    if !source.is_char_boundary(error.offset()) {
+        return None;
+    }
+    let (before, after) = source.split_at(error.offset());
+    let found = after.chars().next()?;
+    let previous = before.trim_end().chars().last();
+
+    if found == '\'' {
+        return Some("JSON strings and keys must use double quotes `\"` not single quotes".into());
+    }
+    if error.kind() != ErrorKind::UnexpectedChar {
+        return None;
+    }
+    if matches!(found, ']' | '}') && previous == Some(',') {
+        return Some(format!(
+            "JSON does not allow a trailing comma, remove the `,` before `{found}`"
+        ));
+    }
+    if matches!(previous, Some('{' | ',')) && (found.is_alphabetic() || found == '_') {
+        let key_end = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(after.len());
+        if after[key_end..].trim_start().starts_with(':') {
+            return Some(format!(
+                "object keys must be strings, write `\"{}\"` instead",
+                &after[..key_end]
+            ));
+        }
+    }
+    None
+}
@@ -1,14 +1,174 @@
 use std::fmt::Display;
 
+/// the kind of problem a [FuzJsonParseError] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// the input was empty or only whitespace
+    EmptyInput,
+    /// the input ended before the value was complete
+    UnexpectedEof,
+    /// a character that can't appear at that point of the document
+    UnexpectedChar,
+    /// an unknown escape sequence or a malformed `\u` escape in a string
+    InvalidEscape,
+    /// a number that doesn't follow the JSON number grammar or doesn't fit its type
+    InvalidNumber,
+    /// a control character that wasn't escaped inside a string
+    ControlCharacter,
+    /// something other than whitespace after the top level value
+    TrailingCharacters,
+    /// an object has the same key more than once and
+    /// [ParseOptions::duplicate_keys](crate::json_parser::ParseOptions::duplicate_keys) is
+    /// [DuplicateKeys::Error](crate::json_parser::DuplicateKeys::Error)
+    DuplicateKey,
+    /// arrays and objects are nested deeper than [ParseOptions::max_depth](crate::json_parser::ParseOptions::max_depth)
+    DepthExceeded,
+    /// the input is longer than [ParseOptions::max_input_len](crate::json_parser::ParseOptions::max_input_len)
+    InputTooLarge,
+    /// a string is longer than [ParseOptions::max_string_len](crate::json_parser::ParseOptions::max_string_len)
+    StringTooLong,
+    /// an array has more elements than [ParseOptions::max_array_len](crate::json_parser::ParseOptions::max_array_len)
+    ArrayTooLong,
+    /// an object has more members than [ParseOptions::max_object_members](crate::json_parser::ParseOptions::max_object_members)
+    TooManyMembers,
+    /// the document has more values than [ParseOptions::max_nodes](crate::json_parser::ParseOptions::max_nodes)
+    TooManyNodes,
+    /// the value doesn't fit the type it is deserialized into, like a missing field or a string
+    /// where a number was expected. Only made by the `serde` feature
+    Custom,
+}
+
+impl ErrorKind {
+    /// check if the error comes from going over one of the `max_*` limits in
+    /// [ParseOptions](crate::json_parser::ParseOptions) other than `max_depth`
+    pub fn is_resource_limit(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::InputTooLarge
+                | ErrorKind::StringTooLong
+                | ErrorKind::ArrayTooLong
+                | ErrorKind::TooManyMembers
+                | ErrorKind::TooManyNodes
+        )
+    }
+}
+
 /// Error type for parsing function
 ///
-/// holds a string describing the error
-#[derive(Debug, PartialEq, Eq)]
-pub struct FuzJsonParseError(pub String);
+/// holds the [ErrorKind], a string describing the error and where in the source it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzJsonParseError {
+    kind: ErrorKind,
+    message: String,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl FuzJsonParseError {
+    /// create an error of `kind` for the character at byte `offset` in `source`
+    ///
+    /// the line and column are worked out from `source`
+    pub fn new<S: Into<String>>(kind: ErrorKind, message: S, source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let (line, column) = line_and_column(source, (0, 1, 1), offset);
+        Self::at_position(kind, message, offset, line, column)
+    }
+
+    /// create an error whose line and column are already known
+    pub(crate) fn at_position<S: Into<String>>(
+        kind: ErrorKind,
+        message: S,
+        offset: usize,
+        line: usize,
+        column: usize,
+    ) -> Self {
+        FuzJsonParseError {
+            kind,
+            message: message.into(),
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// the kind of problem that caused the error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// the string describing the error
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// byte offset in the source of the character that caused the error
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// 1-based line of the error
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column of the error, counted in characters from the start of the line
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// check if the error knows where it happened, errors made by serde through
+    /// [serde::de::Error::custom] don't until the deserializer fills it in
+    pub(crate) fn has_position(&self) -> bool {
+        self.line != 0
+    }
+}
+
+/// the line and column of byte `offset` in `source`, counting on from `from`, a known
+/// `(offset, line, column)` at or before it, so only the text in between is looked at
+pub(crate) fn line_and_column(
+    source: &str,
+    from: (usize, usize, usize),
+    offset: usize,
+) -> (usize, usize) {
+    let (start, mut line, mut column) = from;
+    let between = &source.as_bytes()[start..offset];
+    // continuation bytes are the only ones that don't start a character
+    let chars = |bytes: &[u8]| bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+    match between.iter().rposition(|&b| b == b'\n') {
+        Some(last) => {
+            line += between.iter().filter(|&&b| b == b'\n').count();
+            column = chars(&between[last + 1..]) + 1;
+        }
+        None => column += chars(between),
+    }
+    (line, column)
+}
+
+/// the message comes from the `Deserialize` implementation, the deserializer adds the position
+#[cfg(feature = "serde")]
+impl serde::de::Error for FuzJsonParseError {
+    fn custom<T: Display>(msg: T) -> Self {
+        FuzJsonParseError {
+            kind: ErrorKind::Custom,
+            message: msg.to_string(),
+            offset: 0,
+            line: 0,
+            column: 0,
+        }
+    }
+}
 
 impl Display for FuzJsonParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        if self.line == 0 {
+            return write!(f, "{}", self.message);
+        }
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.line, self.column
+        )
     }
 }
 
@@ -17,8 +177,69 @@ pub type Result<T> = std::result::Result<T, FuzJsonParseError>;
 
 impl std::error::Error for FuzJsonParseError {}
 
-impl<S: Into<String>> From<S> for FuzJsonParseError {
-    fn from(from_str_ref: S) -> Self {
-        FuzJsonParseError(from_str_ref.into())
+/// Error type for converting a [JsonValue](crate::values::JsonValue) into a Rust type
+///
+/// says what type was expected, what was found instead and where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    expected: String,
+    found: String,
+    path: String,
+}
+
+impl ConversionError {
+    /// create an error for finding `found` where a value of type `expected` was needed
+    pub fn new<E: Into<String>, F: Into<String>>(expected: E, found: F) -> Self {
+        ConversionError {
+            expected: expected.into(),
+            found: found.into(),
+            path: String::new(),
+        }
+    }
+
+    /// the error for the converted value being inside an array at `index`
+    ///
+    /// used by conversions of containers, called from the innermost value outwards
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.insert_str(0, &format!("/{index}"));
+        self
+    }
+
+    /// the error for the converted value being the member `key` of an object
+    ///
+    /// used by conversions of containers, called from the innermost value outwards
+    pub fn at_key(mut self, key: &str) -> Self {
+        let key = key.replace('~', "~0").replace('/', "~1");
+        self.path.insert_str(0, &format!("/{key}"));
+        self
+    }
+
+    /// the type that was expected
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// a description of the value that was found instead
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// JSON pointer to the value that failed to convert, empty for the value itself
+    ///
+    /// can be given to [JsonValue::pointer](crate::values::JsonValue::pointer) to look at the value
+    pub fn path(&self) -> &str {
+        &self.path
     }
 }
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {} found {}", self.expected, self.found)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConversionError {}
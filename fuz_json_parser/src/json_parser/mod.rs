@@ -1,13 +1,154 @@
-use crate::{create_state, error, values::JsonValue};
+use crate::{create_state, error, value_ref::JsonValueRef, values::JsonValue};
 
+pub mod options;
 pub mod parsers;
 pub mod state;
 
+pub use options::{DuplicateKeys, IntegerOverflow, ParseOptions};
+
+/// parse a json string into a [JsonValue] using the default [ParseOptions]
 pub fn parse<S: AsRef<str>>(json_str: S) -> error::Result<JsonValue> {
-    let mut state = create_state!(json_str);
-    state::consume_whitespace(&mut state);
-    match state::peek(&mut state) {
-        Some(_) => parsers::main_parse(&mut state),
-        None => Ok(JsonValue::Null),
+    parse_with_options(json_str, &ParseOptions::default())
+}
+
+/// parse a json string into a [JsonValue], `options` decides which relaxations of RFC 8259 are accepted
+///
+/// anything other than whitespace after the value is an error,
+/// use [parse_partial_with_options] to parse a value followed by more content
+pub fn parse_with_options<S: AsRef<str>>(
+    json_str: S,
+    options: &ParseOptions,
+) -> error::Result<JsonValue> {
+    let mut state = create_state!(json_str, *options);
+    let value = parse_value(&mut state)?;
+    check_trailing(&mut state)?;
+    Ok(value)
+}
+
+/// parse a json string into a [JsonValueRef] that borrows from it, using the default [ParseOptions]
+///
+/// see [parse_borrowed_with_options]
+pub fn parse_borrowed(json_str: &str) -> error::Result<JsonValueRef<'_>> {
+    parse_borrowed_with_options(json_str, &ParseOptions::default())
+}
+
+/// parse a json string into a [JsonValueRef], strings and keys without escapes are
+/// borrowed from `json_str` instead of copied
+///
+/// accepts the same input as [parse_with_options]
+/// ```
+/// use std::borrow::Cow;
+/// use fuz_json_parser::{json_parser::parse_borrowed, value_ref::JsonValueRef};
+///
+/// let value = parse_borrowed(r#"{"plain": "text", "escaped": "a\nb"}"#).unwrap();
+/// assert!(matches!(value["plain"], JsonValueRef::String(Cow::Borrowed("text"))));
+/// assert!(matches!(&value["escaped"], JsonValueRef::String(Cow::Owned(s)) if s == "a\nb"));
+/// ```
+pub fn parse_borrowed_with_options<'a>(
+    json_str: &'a str,
+    options: &ParseOptions,
+) -> error::Result<JsonValueRef<'a>> {
+    let mut state = create_state!(json_str, *options);
+    let value = parse_value(&mut state)?;
+    check_trailing(&mut state)?;
+    Ok(value)
+}
+
+/// parse the first json value in a string using the default [ParseOptions]
+///
+/// see [parse_partial_with_options]
+pub fn parse_partial<S: AsRef<str>>(json_str: S) -> error::Result<(JsonValue, usize)> {
+    parse_partial_with_options(json_str, &ParseOptions::default())
+}
+
+/// parse the first json value in a string and return it with the byte offset right after it
+///
+/// unlike [parse_with_options] content after the value is not looked at,
+/// so concatenated values can be read by parsing again from the returned offset
+/// ```
+/// use fuz_json_parser::json_parser::parse_partial;
+///
+/// let json_str = "{\"a\": 1} [2] 3";
+/// let (_, end) = parse_partial(json_str).unwrap();
+/// assert_eq!(end, 8);
+/// let (_, next_end) = parse_partial(&json_str[end..]).unwrap();
+/// assert_eq!(&json_str[end..end + next_end], " [2]");
+/// ```
+pub fn parse_partial_with_options<S: AsRef<str>>(
+    json_str: S,
+    options: &ParseOptions,
+) -> error::Result<(JsonValue, usize)> {
+    let mut state = create_state!(json_str, *options);
+    let value = parse_value(&mut state)?;
+    Ok((value, state.position()))
+}
+
+/// parse a json string using the default [ParseOptions] and report every error instead of only the first
+///
+/// see [parse_recovering_with_options]
+pub fn parse_recovering<S: AsRef<str>>(json_str: S) -> (JsonValue, Vec<error::FuzJsonParseError>) {
+    parse_recovering_with_options(json_str, &ParseOptions::default())
+}
+
+/// parse a json string and report every error instead of stopping at the first
+///
+/// after an error the parser skips ahead to the next `,`, `]` or `}` and carries on.
+/// the returned value holds everything that could be parsed, elements and members that
+/// failed are left out. The list of errors is empty when the whole string is valid
+/// ```
+/// use fuz_json_parser::json_parser::parse_recovering;
+///
+/// let (value, errors) = parse_recovering("[1, x, 3, {\"a\": y}]");
+/// assert_eq!(value.encode(), "[1,3,{}]");
+/// assert_eq!(errors.len(), 2);
+/// ```
+pub fn parse_recovering_with_options<S: AsRef<str>>(
+    json_str: S,
+    options: &ParseOptions,
+) -> (JsonValue, Vec<error::FuzJsonParseError>) {
+    let mut state = create_state!(json_str, *options);
+    state.start_recovering();
+    let value = match parse_value(&mut state) {
+        Ok(value) => value,
+        Err(e) => {
+            // only errors that stop parsing get here so the rest of the input is not looked at
+            state.record_error(e);
+            return (JsonValue::Null, state.take_errors());
+        }
+    };
+    if let Err(e) = check_trailing(&mut state) {
+        state.record_error(e);
+    }
+    (value, state.take_errors())
+}
+
+/// make sure there is nothing but whitespace left after the value
+pub(crate) fn check_trailing(state: &mut state::ParserState) -> error::Result<()> {
+    state::consume_whitespace(state);
+    match state.peek_char() {
+        Some(c) => Err(state.error(
+            error::ErrorKind::TrailingCharacters,
+            format!(
+                "trailing characters after value found `{}`",
+                c.escape_debug()
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// skip leading whitespace and parse one value
+///
+/// empty input is an [ErrorKind::EmptyInput](error::ErrorKind::EmptyInput) error
+/// unless [ParseOptions::allow_empty_input] is set
+fn parse_value<'a, V: parsers::ParsedValue<'a>>(
+    state: &mut state::ParserState<'a>,
+) -> error::Result<V> {
+    state.check_input_len()?;
+    state::consume_whitespace(state);
+    match state::peek(state) {
+        Some(_) => parsers::main_parse(state),
+        None if state.options.allow_empty_input => Ok(V::null()),
+        None => Err(state.error(error::ErrorKind::EmptyInput, "input is empty")),
     }
 }
@@ -0,0 +1,133 @@
+/// default for [ParseOptions::max_depth]
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// what the parser does when an object has the same key more than once, see
+/// [ParseOptions::duplicate_keys]
+///
+/// RFC 8259 leaves this up to the parser, and parsers that pick differently can read
+/// different values out of the same document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// fail with [ErrorKind::DuplicateKey](crate::error::ErrorKind::DuplicateKey) pointing at the second key
+    Error,
+    /// keep the value of the first member with the key
+    FirstWins,
+    /// keep the value of the last member with the key
+    LastWins,
+    /// keep every value for the key in an array, in the order they appear
+    ///
+    /// keys that appear once keep their value as is
+    Collect,
+}
+
+/// what the parser does with an integer that doesn't fit in an `i128` or `u128`, see
+/// [ParseOptions::integer_overflow]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerOverflow {
+    /// fail with [ErrorKind::InvalidNumber](crate::error::ErrorKind::InvalidNumber)
+    Error,
+    /// parse it as the nearest [JsonNum::Float](crate::values::JsonNum::Float), losing precision
+    Float,
+    /// keep it as [JsonNum::Raw](crate::values::JsonNum::Raw) so no digits are lost
+    Raw,
+}
+
+/// settings that change which documents the parser accepts
+///
+/// [ParseOptions::strict] follows RFC 8259 exactly and is also the [Default],
+/// [ParseOptions::lenient] turns every relaxation on. Each relaxation is a separate
+/// field so they can be picked one by one
+///
+/// the `max_*` limits are for untrusted input, they are all off (`None`) in both presets.
+/// Going over a limit stops parsing right away, even in recovery mode
+/// ```
+/// use fuz_json_parser::json_parser::{parse_with_options, ParseOptions};
+///
+/// let options = ParseOptions {
+///     case_insensitive_literals: true,
+///     ..ParseOptions::strict()
+/// };
+/// assert!(parse_with_options("TRUE", &options).is_ok());
+/// assert!(parse_with_options(".5", &options).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// accept `true`, `false` and `null` in any case, like `TRUE` or `False`
+    pub case_insensitive_literals: bool,
+    /// accept numbers without a digit before the decimal point, like `.5` or `-.5`
+    pub allow_leading_decimal_point: bool,
+    /// parse input that is empty or only whitespace as `null` instead of
+    /// returning an [ErrorKind::EmptyInput](crate::error::ErrorKind::EmptyInput) error
+    pub allow_empty_input: bool,
+    /// how many arrays and objects can be nested inside each other before parsing fails with
+    /// [ErrorKind::DepthExceeded](crate::error::ErrorKind::DepthExceeded), this keeps hostile
+    /// input from overflowing the stack so raise it with care
+    pub max_depth: usize,
+    /// largest input in bytes, longer input fails with
+    /// [ErrorKind::InputTooLarge](crate::error::ErrorKind::InputTooLarge) before anything is parsed
+    pub max_input_len: Option<usize>,
+    /// longest string or object key in bytes after unescaping, see
+    /// [ErrorKind::StringTooLong](crate::error::ErrorKind::StringTooLong)
+    pub max_string_len: Option<usize>,
+    /// most elements in one array, see [ErrorKind::ArrayTooLong](crate::error::ErrorKind::ArrayTooLong)
+    pub max_array_len: Option<usize>,
+    /// most members in one object, see [ErrorKind::TooManyMembers](crate::error::ErrorKind::TooManyMembers)
+    pub max_object_members: Option<usize>,
+    /// most values in the whole document counting every array, object and scalar, see
+    /// [ErrorKind::TooManyNodes](crate::error::ErrorKind::TooManyNodes)
+    pub max_nodes: Option<usize>,
+    /// how repeated keys in an object are handled, [DuplicateKeys::LastWins] in both presets
+    pub duplicate_keys: DuplicateKeys,
+    /// keep every number as [JsonNum::Raw](crate::values::JsonNum::Raw) holding its source text
+    /// so that no digits are lost and it is encoded exactly as it was written,
+    /// the `as_*` methods on [JsonNum](crate::values::JsonNum) convert it with overflow checks
+    pub arbitrary_precision: bool,
+    /// how integers too large for both `i128` and `u128` are handled,
+    /// [IntegerOverflow::Error] in both presets
+    pub integer_overflow: IntegerOverflow,
+}
+
+impl ParseOptions {
+    /// options that only accept documents following RFC 8259
+    pub const fn strict() -> Self {
+        ParseOptions {
+            case_insensitive_literals: false,
+            allow_leading_decimal_point: false,
+            allow_empty_input: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_input_len: None,
+            max_string_len: None,
+            max_array_len: None,
+            max_object_members: None,
+            max_nodes: None,
+            duplicate_keys: DuplicateKeys::LastWins,
+            arbitrary_precision: false,
+            integer_overflow: IntegerOverflow::Error,
+        }
+    }
+
+    /// options with every relaxation turned on
+    pub const fn lenient() -> Self {
+        ParseOptions {
+            case_insensitive_literals: true,
+            allow_leading_decimal_point: true,
+            allow_empty_input: true,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_input_len: None,
+            max_string_len: None,
+            max_array_len: None,
+            max_object_members: None,
+            max_nodes: None,
+            duplicate_keys: DuplicateKeys::LastWins,
+            arbitrary_precision: false,
+            integer_overflow: IntegerOverflow::Error,
+        }
+    }
+}
+
+/// Default for `ParseOptions` is [ParseOptions::strict]
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions::strict()
+    }
+}
@@ -1,156 +1,633 @@
+use super::options::{DuplicateKeys, IntegerOverflow};
 use super::state::*;
-use crate::error::Result;
-use crate::values::{JsonNum, JsonValue};
+use crate::error::{ErrorKind, Result};
+use crate::value_ref::JsonValueRef;
+use crate::values::{JsonNum, JsonValue, Map};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::hash::Hash;
 
-/// error description to use whenever unexpectedly reaching the end of the source string
-const UNEXPECTED_END_OF_STRING: &str = "Invalid JSON\t unexpected end of string";
+/// a value the parser functions can build, implemented by [JsonValue] and [JsonValueRef]
+///
+/// the parser is generic over this so the same code builds owned values and values
+/// that borrow their strings from the source
+pub trait ParsedValue<'a>: Sized {
+    /// the type used for the keys of objects
+    type Key: Eq + Hash + AsRef<str>;
+
+    /// build `null`
+    fn null() -> Self;
+    /// build a boolean
+    fn bool(value: bool) -> Self;
+    /// build a number
+    fn num(value: JsonNum) -> Self;
+    /// build a string, borrowed when it had no escapes
+    fn string(value: Cow<'a, str>) -> Self;
+    /// build an object key, borrowed when it had no escapes
+    fn key(value: Cow<'a, str>) -> Self::Key;
+    /// build an array
+    fn array(values: Vec<Self>) -> Self;
+    /// build an object
+    fn object(members: Map<Self::Key, Self>) -> Self;
+}
+
+impl<'a> ParsedValue<'a> for JsonValue {
+    type Key = String;
+
+    fn null() -> Self {
+        JsonValue::Null
+    }
+    fn bool(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+    fn num(value: JsonNum) -> Self {
+        JsonValue::Num(value)
+    }
+    fn string(value: Cow<'a, str>) -> Self {
+        JsonValue::String(value.into_owned())
+    }
+    fn key(value: Cow<'a, str>) -> Self::Key {
+        value.into_owned()
+    }
+    fn array(values: Vec<Self>) -> Self {
+        JsonValue::Array(values)
+    }
+    fn object(members: Map<Self::Key, Self>) -> Self {
+        JsonValue::Obj(members)
+    }
+}
+
+impl<'a> ParsedValue<'a> for JsonValueRef<'a> {
+    type Key = Cow<'a, str>;
+
+    fn null() -> Self {
+        JsonValueRef::Null
+    }
+    fn bool(value: bool) -> Self {
+        JsonValueRef::Bool(value)
+    }
+    fn num(value: JsonNum) -> Self {
+        JsonValueRef::Num(value)
+    }
+    fn string(value: Cow<'a, str>) -> Self {
+        JsonValueRef::String(value)
+    }
+    fn key(value: Cow<'a, str>) -> Self::Key {
+        value
+    }
+    fn array(values: Vec<Self>) -> Self {
+        JsonValueRef::Array(values)
+    }
+    fn object(members: Map<Self::Key, Self>) -> Self {
+        JsonValueRef::Obj(members)
+    }
+}
 
 /// parse [JsonValue::Num](crate::values::JsonValue::Num) from [ParserState]
-pub fn parse_number(state: &mut ParserState) -> Result<JsonValue> {
-    //!
-    //! return will be a [JsonValue::Num](crate::values::JsonValue::Num) containing either
-    //! [JsonNum::Int](crate::values::JsonNum::Int) or [JsonNum::Float](crate::values::JsonNum::Float)
+///
+/// return will be a [JsonValue::Num](crate::values::JsonValue::Num) containing either
+/// [JsonNum::Int](crate::values::JsonNum::Int) or [JsonNum::Float](crate::values::JsonNum::Float),
+/// [JsonNum::UInt](crate::values::JsonNum::UInt) for integers above `i128::MAX`,
+/// or [JsonNum::Raw](crate::values::JsonNum::Raw) with
+/// [ParseOptions::arbitrary_precision](super::ParseOptions::arbitrary_precision)
+pub fn parse_number<'a, V: ParsedValue<'a>>(state: &mut ParserState<'a>) -> Result<V> {
+    if !state.options.arbitrary_precision {
+        if let Some(num) = parse_plain_number(state) {
+            return Ok(V::num(num));
+        }
+    }
+    let start = state.position();
+    let number_string = consume_number(state)?;
+    if state.options.arbitrary_precision {
+        // `.5` from ParseOptions::allow_leading_decimal_point gets its zero back so it encodes as valid JSON
+        let raw = match number_string.strip_prefix('-') {
+            Some(rest) if rest.starts_with('.') => format!("-0{rest}"),
+            _ if number_string.starts_with('.') => format!("0{number_string}"),
+            _ => number_string.to_string(),
+        };
+        return Ok(V::num(JsonNum::Raw(raw)));
+    }
+
+    let (negative, digits) = match number_string.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number_string),
+    };
+    let is_float = digits.bytes().any(|b| matches!(b, b'.' | b'e' | b'E'));
+
+    if !is_float && digits.len() <= 18 {
+        // up to 18 digits always fit in an i64 so the digits can be added up directly
+        let int = digits
+            .bytes()
+            .fold(0i64, |int, b| int * 10 + (b - b'0') as i64);
+        return Ok(V::num(JsonNum::Int(
+            if negative { -int } else { int } as i128
+        )));
+    }
+    if is_float {
+        return Ok(V::num(JsonNum::Float(parse_float(
+            state,
+            start,
+            number_string,
+        )?)));
+    }
+    if let Ok(int) = number_string.parse() {
+        return Ok(V::num(JsonNum::Int(int)));
+    }
+    if let (false, Ok(uint)) = (negative, number_string.parse()) {
+        return Ok(V::num(JsonNum::UInt(uint)));
+    }
+    match state.options.integer_overflow {
+        IntegerOverflow::Error => Err(state.error_at(
+            start,
+            ErrorKind::InvalidNumber,
+            format!(
+                "integer `{}` is out of range for i128 and u128",
+                number_string
+            ),
+        )),
+        IntegerOverflow::Float => Ok(V::num(JsonNum::Float(parse_float(
+            state,
+            start,
+            number_string,
+        )?))),
+        IntegerOverflow::Raw => Ok(V::num(JsonNum::Raw(number_string.to_string()))),
+    }
+}
+
+/// powers of ten that an `f64` holds exactly
+const EXACT_POWERS_OF_TEN: [f64; 19] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18,
+];
 
-    let number_string = consume_number(state);
+/// read a number without an exponent in one pass, adding up the digits as they are checked
+///
+/// integers of up to 18 digits and fractions whose digits fit in 53 bits are converted
+/// straight from the digits, which is exact. Longer fractions are handed to `str::parse`
+/// without checking the grammar again. Anything else returns `None` with the cursor where it
+/// was, [consume_number] then reports the error or the slower conversions take over
+#[inline]
+fn parse_plain_number(state: &mut ParserState) -> Option<JsonNum> {
+    let bytes = state.remaining();
+    let negative = bytes.first() == Some(&b'-');
+    let int_start = negative as usize;
+    let mut mantissa = 0u64;
+    let mut end = add_digits(bytes, int_start, &mut mantissa);
+    let int_digits = end - int_start;
+    if int_digits == 0 || (int_digits > 1 && bytes[int_start] == b'0') {
+        return None;
+    }
 
-    if number_string.contains('.') {
-        // let number_string = format!("{}.{}", number_string, consume_number(state));
+    let mut fraction_digits = 0;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_start = end + 1;
+        end = add_digits(bytes, fraction_start, &mut mantissa);
+        fraction_digits = end - fraction_start;
+        if fraction_digits == 0 {
+            return None;
+        }
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        return None;
+    }
 
-        match number_string.parse() {
-            Ok(float) => Ok(JsonValue::Num(JsonNum::Float(float))),
-            Err(e) => Err(format!("failed to parse number as f64 {}", e).into()),
+    let num = if fraction_digits == 0 {
+        if int_digits > 18 {
+            return None;
         }
+        // up to 18 digits always fit in an i64
+        let int = mantissa as i64;
+        JsonNum::Int(if negative { -int } else { int } as i128)
+    } else if int_digits + fraction_digits <= 18 && mantissa <= 1 << 53 {
+        // both are exact so the division rounds once, the same as parsing the text
+        let float = mantissa as f64 / EXACT_POWERS_OF_TEN[fraction_digits];
+        JsonNum::Float(if negative { -float } else { float })
     } else {
-        match number_string.parse() {
-            Ok(int) => Ok(JsonValue::Num(JsonNum::Int(int))),
-            Err(e) => Err(format!("failed to parse number as i128 {}", e).into()),
+        let float = state.source()[state.position()..state.position() + end]
+            .parse::<f64>()
+            .ok()
+            .filter(|float| float.is_finite())?;
+        JsonNum::Float(float)
+    };
+    state.skip(end);
+    Some(num)
+}
+
+/// add the digits from `start` onwards to `mantissa`, returns where the digits end
+///
+/// the mantissa wraps once there are more than 19 digits, callers check the count
+#[inline]
+fn add_digits(bytes: &[u8], start: usize, mantissa: &mut u64) -> usize {
+    let mut end = start;
+    // eight digits at a time, see "Fast numeric string to int" by Johnny Lee
+    while let Some(chunk) = bytes.get(end..end + 8) {
+        let chunk = u64::from_le_bytes(chunk.try_into().unwrap());
+        let digits = chunk.wrapping_sub(0x3030_3030_3030_3030);
+        // every byte is a digit when subtracting `0` doesn't borrow and adding 6 doesn't carry
+        // past 9
+        if (chunk & 0xF0F0_F0F0_F0F0_F0F0) != 0x3030_3030_3030_3030
+            || (digits.wrapping_add(0x0606_0606_0606_0606) & 0xF0F0_F0F0_F0F0_F0F0) != 0
+        {
+            break;
         }
+        let pairs = digits.wrapping_mul(10).wrapping_add(digits >> 8);
+        let value = (pairs & 0x0000_00FF_0000_00FF)
+            .wrapping_mul(100 + (1_000_000 << 32))
+            .wrapping_add(((pairs >> 16) & 0x0000_00FF_0000_00FF).wrapping_mul(1 + (10_000 << 32)))
+            >> 32;
+        *mantissa = mantissa.wrapping_mul(100_000_000).wrapping_add(value);
+        end += 8;
+    }
+    while let Some(&b @ b'0'..=b'9') = bytes.get(end) {
+        *mantissa = mantissa.wrapping_mul(10).wrapping_add((b - b'0') as u64);
+        end += 1;
+    }
+    end
+}
+
+/// convert a number already checked by [consume_number] to a finite `f64`
+fn parse_float(state: &ParserState, start: usize, number_string: &str) -> Result<f64> {
+    match number_string.parse::<f64>() {
+        Ok(float) if float.is_finite() => Ok(float),
+        Ok(_) => Err(state.error_at(
+            start,
+            ErrorKind::InvalidNumber,
+            format!("number `{}` is out of range for f64", number_string),
+        )),
+        Err(e) => Err(state.error_at(
+            start,
+            ErrorKind::InvalidNumber,
+            format!("failed to parse number as f64 {}", e),
+        )),
     }
 }
 
 /// consumes an escape sequence and returns the intended character
+///
+/// the cursor should be on the character after the `\`
 pub fn parse_escape_sequence(state: &mut ParserState) -> Result<char> {
-    match advance(state) {
-        Some('n') => Ok('\n'),
-        Some('t') => Ok('\t'),
-        Some('r') => Ok('\r'),
-        Some('u') => todo!("add escape for unicode"),
-        Some('"') => Ok('"'),
-        Some('\\') => Ok('\\'),
-        None => Err(UNEXPECTED_END_OF_STRING.into()),
-        Some(c) => Err(format!(
-            "invalid character escape at {}\tattempted escape character`{}`",
-            state
-                .peek()
-                .map_or("UNKOWN".into(), |(pos, _)| pos.to_string()),
-            c
-        )
-        .into()),
+    let escaped = match peek(state) {
+        Some(b'n') => '\n',
+        Some(b't') => '\t',
+        Some(b'r') => '\r',
+        Some(b'b') => '\u{8}',
+        Some(b'f') => '\u{c}',
+        Some(b'/') => '/',
+        Some(b'"') => '"',
+        Some(b'\\') => '\\',
+        Some(b'u') => {
+            advance(state);
+            return parse_unicode_escape(state);
+        }
+        Some(_) => {
+            return Err(state.error(
+                ErrorKind::InvalidEscape,
+                format!(
+                    "invalid character escape `\\{}`",
+                    state.peek_char().unwrap_or_default().escape_debug()
+                ),
+            ))
+        }
+        None => return Err(unexpected(state, "escape character")),
+    };
+    advance(state);
+    Ok(escaped)
+}
+
+/// consumes the four hex digits of a `\uXXXX` escape and returns the code unit
+fn parse_hex_quad(state: &mut ParserState) -> Result<u16> {
+    let mut code_unit = 0;
+    for _ in 0..4 {
+        let digit = match peek(state) {
+            Some(c) => (c as char).to_digit(16).ok_or_else(|| {
+                state.error(
+                    ErrorKind::InvalidEscape,
+                    format!(
+                        "invalid unicode escape, expected hex digit found `{}`",
+                        state.peek_char().unwrap_or_default().escape_debug()
+                    ),
+                )
+            })?,
+            None => return Err(unexpected(state, "hex digit")),
+        };
+        advance(state);
+        code_unit = (code_unit << 4) | digit as u16;
     }
+    Ok(code_unit)
+}
+
+/// consumes the rest of a `\u` escape and returns the intended character
+///
+/// the cursor should be on the character after the `\u`.
+/// a high surrogate must be followed by a `\uXXXX` low surrogate, the pair is combined into one character
+pub fn parse_unicode_escape(state: &mut ParserState) -> Result<char> {
+    // points at the `\` of the escape
+    let start = state.position().saturating_sub(2);
+    let first = parse_hex_quad(state)?;
+    let code_point = match first {
+        0xD800..=0xDBFF => {
+            if !(check_char(state, b'\\') && check_char(state, b'u')) {
+                return Err(state.error_at(
+                    start,
+                    ErrorKind::InvalidEscape,
+                    format!(
+                        "invalid unicode escape, high surrogate `\\u{:04X}` is not followed by a low surrogate",
+                        first
+                    ),
+                ));
+            }
+            let second = parse_hex_quad(state)?;
+            if !(0xDC00..=0xDFFF).contains(&second) {
+                return Err(state.error_at(
+                    start,
+                    ErrorKind::InvalidEscape,
+                    format!(
+                        "invalid unicode escape, high surrogate `\\u{:04X}` is followed by `\\u{:04X}` which is not a low surrogate",
+                        first, second
+                    ),
+                ));
+            }
+            0x10000 + (((first as u32) - 0xD800) << 10) + ((second as u32) - 0xDC00)
+        }
+        0xDC00..=0xDFFF => {
+            return Err(state.error_at(
+                start,
+                ErrorKind::InvalidEscape,
+                format!(
+                    "invalid unicode escape, low surrogate `\\u{:04X}` is not preceded by a high surrogate",
+                    first
+                ),
+            ))
+        }
+        _ => first as u32,
+    };
+    // surrogates are handled above so every remaining code point is a valid char
+    char::from_u32(code_point)
+        .ok_or_else(|| state.error_at(start, ErrorKind::InvalidEscape, "invalid unicode escape"))
+}
+
+/// check if a byte ends a run of characters that can be copied into a string as is
+#[inline]
+fn is_string_special(b: u8) -> bool {
+    b == b'"' || b == b'\\' || b < 0x20
 }
 
 /// parse string from cursor postion until ending `"`
-pub fn parse_string(state: &mut ParserState) -> Result<String> {
-    assert_char(state, '"', false)?;
-    let mut working_stirng = String::new();
+///
+/// the string is borrowed from the source unless it has escapes, then runs of
+/// characters without escapes are copied into a new string in one go
+pub fn parse_string<'a>(state: &mut ParserState<'a>) -> Result<Cow<'a, str>> {
+    let start = state.position();
+    assert_char(state, b'"', false)?;
+    let max_len = state.options.max_string_len.unwrap_or(usize::MAX);
+    let mut working_stirng = Cow::Borrowed("");
     loop {
-        let c = match advance(state) {
-            Some(c) => c,
-            None => return Err(UNEXPECTED_END_OF_STRING.into()),
-        };
-        match c {
-            '"' => break,
-            '\\' => working_stirng.push(parse_escape_sequence(state)?),
-            c => working_stirng.push(c),
+        let run = state
+            .remaining()
+            .iter()
+            .position(|&b| is_string_special(b))
+            .unwrap_or(state.remaining().len());
+        let run_start = state.position();
+        state.skip(run);
+        // runs stop at ASCII bytes so they always end on a character boundary
+        let run = state.slice_from(run_start);
+        // checked before the run is copied so a long string never gets allocated
+        check_string_len(state, start, working_stirng.len() + run.len(), max_len)?;
+        if working_stirng.is_empty() {
+            working_stirng = Cow::Borrowed(run);
+        } else {
+            working_stirng.to_mut().push_str(run);
+        }
+
+        match peek(state) {
+            Some(b'"') => {
+                advance(state);
+                break;
+            }
+            Some(b'\\') => {
+                advance(state);
+                match parse_escape_sequence(state) {
+                    Ok(c) => {
+                        check_string_len(
+                            state,
+                            start,
+                            working_stirng.len() + c.len_utf8(),
+                            max_len,
+                        )?;
+                        working_stirng.to_mut().push(c)
+                    }
+                    // the rest of the string can still be read after a bad escape
+                    Err(e) if e.kind() == ErrorKind::InvalidEscape => state.recover(e)?,
+                    Err(e) => return Err(e),
+                }
+            }
+            Some(c) => {
+                let e = state.error(
+                    ErrorKind::ControlCharacter,
+                    format!(
+                        "control characters must be escaped in strings found `{}`",
+                        (c as char).escape_default()
+                    ),
+                );
+                state.recover(e)?;
+                advance(state);
+                check_string_len(state, start, working_stirng.len() + 1, max_len)?;
+                working_stirng.to_mut().push(c as char)
+            }
+            None => return Err(unexpected(state, "`\"` to end the string")),
         }
     }
 
     Ok(working_stirng)
 }
 
+/// fail with [ErrorKind::StringTooLong] pointing at the string at `start` when growing it
+/// to `len` bytes would pass `max_len`
+fn check_string_len(state: &ParserState, start: usize, len: usize, max_len: usize) -> Result<()> {
+    if len > max_len {
+        return Err(state.error_at(
+            start,
+            ErrorKind::StringTooLong,
+            format!("string is longer than the limit of {max_len} bytes"),
+        ));
+    }
+    Ok(())
+}
+
+/// parse one `"key": value` member of an object
+fn parse_member<'a, V: ParsedValue<'a>>(state: &mut ParserState<'a>) -> Result<(V::Key, V)> {
+    let key = V::key(parse_string(state)?);
+    consume_whitespace(state);
+    assert_char(state, b':', false)?;
+    consume_whitespace(state);
+    Ok((key, main_parse(state)?))
+}
+
+/// consume the `,` or `close` character after an element of an array or object
+///
+/// returns `true` once the array or object is closed. In recovery mode anything else is
+/// reported and skipped, if the next sync point is not a `,` or `close` the array or object is
+/// treated as closed and the character is left for the parent to deal with
+fn end_of_element(state: &mut ParserState, close: u8) -> Result<bool> {
+    consume_whitespace(state);
+    match peek(state) {
+        Some(b',') => {
+            advance(state);
+            consume_whitespace(state);
+            return Ok(false);
+        }
+        Some(c) if c == close => {
+            advance(state);
+            return Ok(true);
+        }
+        _ => {
+            let e = unexpected(state, &format!("either `,` or `{}`", close as char));
+            state.recover(e)?;
+            skip_to_sync_point(state);
+        }
+    }
+    match peek(state) {
+        Some(b',') => {
+            advance(state);
+            consume_whitespace(state);
+            Ok(false)
+        }
+        Some(c) if c == close => {
+            advance(state);
+            Ok(true)
+        }
+        _ => Ok(true),
+    }
+}
+
 /// parse [JsonValue::Obj](crate::values::JsonValue::Obj) from [ParserState]
-pub fn parse_object(state: &mut ParserState) -> Result<JsonValue> {
-    assert_char(state, '{', false)?;
+pub fn parse_object<'a, V: ParsedValue<'a>>(state: &mut ParserState<'a>) -> Result<V> {
+    let start = state.position();
+    // the depth is checked before consuming `{` so recovery skips the whole value
+    state.enter_nested(start)?;
+    assert_char(state, b'{', false).inspect_err(|_| state.leave_nested())?;
     consume_whitespace(state);
-    let mut json_map: HashMap<String, JsonValue> = HashMap::new();
-    if let Some('}') = peek(state) {
-        state.next();
-        return Ok(JsonValue::Obj(json_map));
+    let mut json_map: Map<V::Key, V> = Map::new();
+    if check_char(state, b'}') {
+        state.leave_nested();
+        return Ok(V::object(json_map));
     }
+    let max_members = state.options.max_object_members.unwrap_or(usize::MAX);
+    let duplicate_keys = state.options.duplicate_keys;
+    // values of repeated keys for DuplicateKeys::Collect, added to the object once it is closed,
+    // a plain `HashMap` because it doesn't allocate until a key repeats
+    let mut collected: HashMap<V::Key, Vec<V>> = HashMap::new();
+    let mut members = 0;
     loop {
-        let key = parse_string(state)?;
-        consume_whitespace(state);
-        assert_char(state, ':', false)?;
-        consume_whitespace(state);
-
-        json_map.insert(key, main_parse(state)?);
-        consume_whitespace(state);
-
-        match advance(state){
-                Some(',') => consume_whitespace(state),
-                Some('}') => break,
-                None => return Err(UNEXPECTED_END_OF_STRING.into()),
-                Some(c) => return Err(format!("Invalid json string error at position {} expected either `,` or `}}` instead found {}", state.peek().map_or("UNKOWN".into(), |(pos, _)|pos.to_string()), c).into())
+        if members == max_members {
+            return Err(state.error(
+                ErrorKind::TooManyMembers,
+                format!("object has more members than the limit of {max_members}"),
+            ));
+        }
+        members += 1;
+        let key_start = state.position();
+        match parse_member(state) {
+            Ok((key, value)) => {
+                if duplicate_keys == DuplicateKeys::LastWins || !json_map.contains_key(&key) {
+                    json_map.insert(key, value);
+                } else {
+                    match duplicate_keys {
+                        DuplicateKeys::Error => {
+                            let e = state.error_at(
+                                key_start,
+                                ErrorKind::DuplicateKey,
+                                format!(
+                                    "duplicate key `{}` in object",
+                                    key.as_ref().escape_debug()
+                                ),
+                            );
+                            state.recover(e)?;
+                        }
+                        DuplicateKeys::Collect => collected.entry(key).or_default().push(value),
+                        DuplicateKeys::FirstWins | DuplicateKeys::LastWins => (),
+                    }
+                }
             }
+            Err(e) => {
+                state.recover(e)?;
+                skip_to_sync_point(state);
+            }
+        }
+        if end_of_element(state, b'}')? {
+            break;
+        }
     }
-    Ok(JsonValue::Obj(json_map))
+    state.leave_nested();
+    for (key, values) in collected {
+        if let Some(first) = json_map.get_mut(&key) {
+            let mut all = vec![std::mem::replace(first, V::null())];
+            all.extend(values);
+            *first = V::array(all);
+        }
+    }
+    Ok(V::object(json_map))
 }
 
 /// parse [JsonValue::Array](crate::values::JsonValue::Array) from [ParserState]
-pub fn parse_array(state: &mut ParserState) -> Result<JsonValue> {
-    assert_char(state, '[', false)?;
+pub fn parse_array<'a, V: ParsedValue<'a>>(state: &mut ParserState<'a>) -> Result<V> {
+    let start = state.position();
+    // the depth is checked before consuming `[` so recovery skips the whole value
+    state.enter_nested(start)?;
+    assert_char(state, b'[', false).inspect_err(|_| state.leave_nested())?;
     consume_whitespace(state);
-    let mut json_list: Vec<JsonValue> = Vec::new();
-    if let Some(']') = peek(state) {
-        state.next();
-        return Ok(JsonValue::Array(json_list));
+    let mut json_list: Vec<V> = Vec::new();
+    if check_char(state, b']') {
+        state.leave_nested();
+        return Ok(V::array(json_list));
     }
 
+    let max_len = state.options.max_array_len.unwrap_or(usize::MAX);
     loop {
-        json_list.push(main_parse(state)?);
-        consume_whitespace(state);
-        match advance(state){
-                Some(',') => consume_whitespace(state),
-                Some(']') => break,
-                None => return Err(UNEXPECTED_END_OF_STRING.into()),
-                Some(c) => return Err(format!("Invalid json string error at position {}  expected either `,` or `]` instead found {}", state.peek().map_or("UNKOWN".into(), |(pos, _)|pos.to_string()), c).into())
+        if json_list.len() == max_len {
+            return Err(state.error(
+                ErrorKind::ArrayTooLong,
+                format!("array has more elements than the limit of {max_len}"),
+            ));
+        }
+        match main_parse(state) {
+            Ok(value) => json_list.push(value),
+            Err(e) => {
+                state.recover(e)?;
+                skip_to_sync_point(state);
             }
+        }
+        if end_of_element(state, b']')? {
+            break;
+        }
     }
+    state.leave_nested();
 
-    Ok(JsonValue::Array(json_list))
+    Ok(V::array(json_list))
 }
 
 /// the primary parsing function of the [ParserState] that can
-pub fn main_parse(state: &mut ParserState) -> Result<JsonValue> {
+pub fn main_parse<'a, V: ParsedValue<'a>>(state: &mut ParserState<'a>) -> Result<V> {
     consume_whitespace(state);
+    state.count_node()?;
+    let ignore_case = state.options.case_insensitive_literals;
     match peek(state) {
-        Some('t' | 'T') => {
-            assert_string(state, "true", true)?;
-            Ok(JsonValue::Bool(true))
-        }
-        Some('f' | 'F') => {
-            assert_string(state, "false", true)?;
-            Ok(JsonValue::Bool(false))
-        }
-        Some('n') => {
-            assert_string(state, "null", true)?;
-            Ok(JsonValue::Null)
-        }
-        Some('.' | '-' | '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9') => {
-            parse_number(state)
-        }
-
-        Some('"') => Ok(JsonValue::String(parse_string(state)?)),
-        Some('[') => parse_array(state),
-        Some('{') => parse_object(state),
-        None => Err(UNEXPECTED_END_OF_STRING.into()),
-        Some(c) => Err(format!(
-            "Invalid JSON\tunknown character at position: {} `{c}`",
-            state
-                .peek()
-                .map_or("UNKOWN".into(), |(pos, _)| pos.to_string())
-        )
-        .into()),
+        Some(c @ (b't' | b'T')) if ignore_case || c == b't' => {
+            assert_string(state, "true", ignore_case)?;
+            Ok(V::bool(true))
+        }
+        Some(c @ (b'f' | b'F')) if ignore_case || c == b'f' => {
+            assert_string(state, "false", ignore_case)?;
+            Ok(V::bool(false))
+        }
+        Some(c @ (b'n' | b'N')) if ignore_case || c == b'n' => {
+            assert_string(state, "null", ignore_case)?;
+            Ok(V::null())
+        }
+        Some(b'.' | b'-' | b'0'..=b'9') => parse_number(state),
+        Some(b'"') => Ok(V::string(parse_string(state)?)),
+        Some(b'[') => parse_array(state),
+        Some(b'{') => parse_object(state),
+        _ => Err(unexpected(state, "value")),
     }
 }
@@ -1,65 +1,275 @@
-use crate::error::Result;
-use std::{
-    iter::{Enumerate, Peekable},
-    str::Chars,
-};
+// the longer function docs continue as `//!` comments inside the function body
+#![allow(clippy::mixed_attributes_style)]
+
+use super::options::ParseOptions;
+use crate::error::{line_and_column, ErrorKind, FuzJsonParseError, Result};
+use std::cell::Cell;
 
 /// state of parseing function,
-/// holds cursor and string to be parsed
-pub type ParserState<'a> = Peekable<Enumerate<Chars<'a>>>;
-// pub type ParserState = Peekable<Enumerate<impl Iterator<Item = char> + Clone>>;
+/// holds cursor and string to be parsed along with the [ParseOptions] in use
+///
+/// the cursor is a byte offset into the string. Everything outside of strings is ASCII so the
+/// parser works on bytes, the source is a `&str` so it is already valid UTF-8 and string
+/// contents can be sliced out of it between ASCII delimiters without checking again
+pub struct ParserState<'a> {
+    json_str: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    pub options: ParseOptions,
+    /// errors collected in recovery mode, `None` when not recovering
+    errors: Option<Vec<FuzJsonParseError>>,
+    /// how many arrays and objects the cursor is inside of
+    depth: usize,
+    /// how many values have been started, checked against [ParseOptions::max_nodes]
+    nodes: usize,
+    /// `(offset, line, column)` of the last error made, the next error further on counts
+    /// lines from there instead of from the start of the string
+    last_position: Cell<(usize, usize, usize)>,
+}
+
+impl<'a> ParserState<'a> {
+    /// create a [ParserState] with the cursor at the start of `json_str`
+    pub fn new(json_str: &'a str, options: ParseOptions) -> Self {
+        ParserState {
+            json_str,
+            bytes: json_str.as_bytes(),
+            pos: 0,
+            options,
+            errors: None,
+            depth: 0,
+            nodes: 0,
+            last_position: Cell::new((0, 1, 1)),
+        }
+    }
+
+    /// fail with [ErrorKind::InputTooLarge] if the string is longer than [ParseOptions::max_input_len]
+    pub fn check_input_len(&self) -> Result<()> {
+        match self.options.max_input_len {
+            Some(max) if self.json_str.len() > max => Err(self.error_at(
+                0,
+                ErrorKind::InputTooLarge,
+                format!(
+                    "input is {} bytes which is more than the limit of {max}",
+                    self.json_str.len()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// count a value starting at the cursor, fails if there are more than [ParseOptions::max_nodes]
+    #[inline]
+    pub fn count_node(&mut self) -> Result<()> {
+        self.nodes += 1;
+        match self.options.max_nodes {
+            Some(max) if self.nodes > max => Err(self.error(
+                ErrorKind::TooManyNodes,
+                format!("document has more values than the limit of {max}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// go one array or object deeper, fails if that is more than [ParseOptions::max_depth]
+    ///
+    /// `offset` is the position of the opening `[` or `{`, every successful call must be
+    /// matched with a call to [leave_nested](ParserState::leave_nested)
+    pub fn enter_nested(&mut self, offset: usize) -> Result<()> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error_at(
+                offset,
+                ErrorKind::DepthExceeded,
+                format!(
+                    "arrays and objects are nested deeper than the limit of {}",
+                    self.options.max_depth
+                ),
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// go back out of an array or object entered with [enter_nested](ParserState::enter_nested)
+    pub fn leave_nested(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// switch to recovery mode where [recover](ParserState::recover) collects errors instead of returning them
+    pub fn start_recovering(&mut self) {
+        self.errors.get_or_insert_with(Vec::new);
+    }
+
+    /// check if the state is in recovery mode
+    pub fn is_recovering(&self) -> bool {
+        self.errors.is_some()
+    }
+
+    /// in recovery mode keep `error` and return `Ok` so parsing can go on, otherwise return the error
+    ///
+    /// resource limit errors are always returned since going on would defeat the limit
+    pub fn recover(&mut self, error: FuzJsonParseError) -> Result<()> {
+        if !self.is_recovering() || error.kind().is_resource_limit() {
+            return Err(error);
+        }
+        self.record_error(error);
+        Ok(())
+    }
+
+    /// keep `error` in recovery mode, does nothing otherwise
+    ///
+    /// an error at the same position as the previous one is dropped since it describes the same problem
+    pub fn record_error(&mut self, error: FuzJsonParseError) {
+        if let Some(errors) = &mut self.errors {
+            if errors.last().map(|e| e.offset()) != Some(error.offset()) {
+                errors.push(error);
+            }
+        }
+    }
+
+    /// take the errors collected in recovery mode
+    pub fn take_errors(&mut self) -> Vec<FuzJsonParseError> {
+        self.errors.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// byte offset of the cursor, this is the length of the string once everything is consumed
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// the string being parsed
+    #[inline]
+    pub fn source(&self) -> &'a str {
+        self.json_str
+    }
+
+    /// the bytes from the cursor to the end of the string
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+
+    /// move the cursor `count` bytes forward
+    ///
+    /// the cursor must stay on a character boundary, so only skip over bytes that were looked at
+    #[inline]
+    pub fn skip(&mut self, count: usize) {
+        self.pos = (self.pos + count).min(self.bytes.len());
+    }
+
+    /// the string between byte `start` and the cursor
+    #[inline]
+    pub fn slice_from(&self, start: usize) -> &'a str {
+        &self.json_str[start..self.pos]
+    }
+
+    /// the whole character at the cursor, which may be more than one byte
+    pub fn peek_char(&self) -> Option<char> {
+        self.json_str[self.pos..].chars().next()
+    }
+
+    /// create an error of `kind` for the character at the cursor
+    pub fn error<S: Into<String>>(&self, kind: ErrorKind, message: S) -> FuzJsonParseError {
+        let offset = self.position();
+        self.error_at(offset, kind, message)
+    }
+
+    /// create an error of `kind` for the character at byte `offset`
+    pub fn error_at<S: Into<String>>(
+        &self,
+        offset: usize,
+        kind: ErrorKind,
+        message: S,
+    ) -> FuzJsonParseError {
+        let offset = offset.min(self.json_str.len());
+        let mut from = self.last_position.get();
+        if offset < from.0 {
+            from = (0, 1, 1);
+        }
+        let (line, column) = line_and_column(self.json_str, from, offset);
+        self.last_position.set((offset, line, column));
+        FuzJsonParseError::at_position(kind, message, offset, line, column)
+    }
+}
 
 /// create [ParserState] from json string
+///
+/// uses the default [ParseOptions] unless options are given as a second argument
 #[macro_export]
 macro_rules! create_state {
     ($json_str:ident) => {
-        $json_str.as_ref().chars().enumerate().peekable()
+        $crate::json_parser::state::ParserState::new(
+            $json_str.as_ref(),
+            $crate::json_parser::ParseOptions::default(),
+        )
     };
     ($json_str:expr) => {
-        $json_str.chars().enumerate().peekable()
+        $crate::json_parser::state::ParserState::new(
+            $json_str,
+            $crate::json_parser::ParseOptions::default(),
+        )
+    };
+    ($json_str:ident, $options:expr) => {
+        $crate::json_parser::state::ParserState::new($json_str.as_ref(), $options)
+    };
+    ($json_str:expr, $options:expr) => {
+        $crate::json_parser::state::ParserState::new($json_str, $options)
     };
 }
 
-/// look at the current charater
-pub fn peek(state: &mut ParserState) -> Option<char> {
-    state.peek().map(|(_, c)| *c)
+/// look at the current byte
+#[inline]
+pub fn peek(state: &ParserState) -> Option<u8> {
+    state.bytes.get(state.pos).copied()
 }
 
-/// return the current character and cursor to the next position
-pub fn advance(state: &mut ParserState) -> Option<char> {
-    state.next().map(|(_, c)| c)
+/// return the current byte and move the cursor to the next position
+///
+/// on a byte that is not ASCII the cursor ends up inside a character, use
+/// [ParserState::peek_char] and [ParserState::skip] to move over whole characters
+#[inline]
+pub fn advance(state: &mut ParserState) -> Option<u8> {
+    let b = peek(state)?;
+    state.pos += 1;
+    Some(b)
 }
 
-/// assert that the current character is the expected character `c`
-pub fn assert_char(state: &mut ParserState, mut c: char, ignore_case: bool) -> Result<()> {
-    //!
-    //! if `ignore_case` is `true` the check will be preformed without considering the case of the character
-    if ignore_case {
-        c = c.to_ascii_lowercase();
+/// create the error for the character at the cursor not being what was `expected`
+///
+/// the error is [ErrorKind::UnexpectedEof] when the cursor is at the end of the string
+/// and [ErrorKind::UnexpectedChar] otherwise
+pub fn unexpected(state: &ParserState, expected: &str) -> FuzJsonParseError {
+    match state.peek_char() {
+        Some(c) => state.error(
+            ErrorKind::UnexpectedChar,
+            format!("expected {expected} found `{}`", c.escape_debug()),
+        ),
+        None => state.error(
+            ErrorKind::UnexpectedEof,
+            format!("expected {expected} found end of input"),
+        ),
     }
+}
 
-    if let Some(mut advance_value) = advance(state) {
-        if ignore_case {
-            advance_value = advance_value.to_ascii_lowercase();
-            advance_value = advance_value.to_ascii_lowercase();
-        }
-        if advance_value != c {
-            Err(format!("`{advance_value}` is not equal to `{c}`").into())
-        } else {
+/// assert that the current byte is the expected ASCII character `c`
+pub fn assert_char(state: &mut ParserState, c: u8, ignore_case: bool) -> Result<()> {
+    //!
+    //! if `ignore_case` is `true` the check will be preformed without considering the case of the character
+    match peek(state) {
+        Some(found) if found == c || (ignore_case && found.eq_ignore_ascii_case(&c)) => {
+            state.pos += 1;
             Ok(())
         }
-    } else {
-        Err("No char returned".into())
+        _ => Err(unexpected(state, &format!("`{}`", c as char))),
     }
 }
 
-/// check if the current character matches a character `c`
-pub fn check_char(state: &mut ParserState, check_against_char: char) -> bool {
+/// check if the current byte matches the ASCII character `c`
+#[inline]
+pub fn check_char(state: &mut ParserState, check_against_char: u8) -> bool {
     //!
     //! similar to [assert_char] but only consumes the character if it does match, useful for control flow
     //!
-    //! if `ignore_case` is `true` the check will be preformed without considering the case of the character
-    //!
     //! # Example
     //! instead of using [peek] then [advance] if the returned character was equal, you can
     //! use [check_char] then branch based on the returned value
@@ -67,7 +277,7 @@ pub fn check_char(state: &mut ParserState, check_against_char: char) -> bool {
     //! use fuz_json_parser::{create_state, json_parser::state::{ParserState, advance, peek}};
     //!
     //! let mut state:ParserState = create_state!("-127");
-    //! let is_negative = matches!(peek(&mut state), Some('-'));
+    //! let is_negative = matches!(peek(&state), Some(b'-'));
     //!
     //! if is_negative {
     //!     advance(&mut state);
@@ -78,67 +288,153 @@ pub fn check_char(state: &mut ParserState, check_against_char: char) -> bool {
     //! use fuz_json_parser::{create_state, json_parser::state::{check_char, ParserState}};
     //!
     //! let mut state:ParserState = create_state!("-127");
-    //! let is_negative = check_char(&mut state, '-');
+    //! let is_negative = check_char(&mut state, b'-');
     //! ```
-
-    match peek(state) {
-        Some(c) if c == check_against_char => {
-            advance(state);
-            true
-        }
-        _ => false,
+    if peek(state) == Some(check_against_char) {
+        state.pos += 1;
+        true
+    } else {
+        false
     }
 }
 
-/// uses [assert_char] to assert that the next characters are equal to the provided string
+/// uses [assert_char] to assert that the next characters are equal to the provided ASCII string
+///
+/// the error points at the first character that doesn't match
 pub fn assert_string<S: AsRef<str>>(
     state: &mut ParserState,
     string: S,
     ignore_case: bool,
 ) -> Result<()> {
-    for c in string.as_ref().chars() {
-        if let Err(e) = assert_char(state, c, ignore_case) {
-            return Err(format!("failed assert expected \"{}\"\n{e}", string.as_ref()).into());
+    for &c in string.as_ref().as_bytes() {
+        if assert_char(state, c, ignore_case).is_err() {
+            return Err(unexpected(state, &format!("`{}`", string.as_ref())));
         }
     }
     Ok(())
 }
 
-/// create an integer from digits in [state](ParserState)
-pub fn consume_number(state: &mut ParserState) -> String {
+/// consume a number from [state](ParserState) following the JSON number grammar
+pub fn consume_number<'a>(state: &mut ParserState<'a>) -> Result<&'a str> {
     //!
-    //! consumes characters in the [state](ParserState) until a
-    //! character that is not a digit is found
+    //! `[ - ] int [ . digits ] [ (e | E) [ + | - ] digits ]` where `int` is either `0`
+    //! or a digit from `1` to `9` followed by any number of digits
     //!
-    //! the returned value is a [String] containing all the digits
-
-    state
-        .take(
-            state
-                .clone()
-                .take_while(|(_, c)| is_number_part(*c))
-                .count(),
-        )
-        .map(|(_, c)| c)
-        .collect::<String>()
+    //! when [ParseOptions::allow_leading_decimal_point] is set `int` may be left out
+    //!
+    //! the returned value is the whole number as written in the source,
+    //! any character that breaks the grammar is reported with its position
+    let start = state.pos;
+    check_char(state, b'-');
+
+    if state.options.allow_leading_decimal_point && peek(state) == Some(b'.') {
+        // the fraction below needs at least one digit
+    } else if check_char(state, b'0') {
+        if let Some(c @ b'0'..=b'9') = peek(state) {
+            return Err(invalid_number_char(
+                state,
+                c,
+                "leading zeros are not allowed",
+            ));
+        }
+    } else {
+        consume_digits(state)?;
+    }
+
+    if check_char(state, b'.') {
+        consume_digits(state)?;
+    }
+
+    if let Some(b'e' | b'E') = peek(state) {
+        state.pos += 1;
+        if let Some(b'+' | b'-') = peek(state) {
+            state.pos += 1;
+        }
+        consume_digits(state)?;
+    }
+
+    Ok(state.slice_from(start))
+}
+
+/// consume one or more digits used by [consume_number]
+fn consume_digits(state: &mut ParserState) -> Result<()> {
+    match peek(state) {
+        Some(b'0'..=b'9') => (),
+        Some(c) => return Err(invalid_number_char(state, c, "expected digit")),
+        None => return Err(unexpected(state, "digit")),
+    }
+    while let Some(b'0'..=b'9') = peek(state) {
+        state.pos += 1;
+    }
+    Ok(())
 }
 
-/// check if the character at the cursor is a digit used by [consume_number]
-fn is_number_part(character: char) -> bool {
-    match character {
-        '-' | '.' => true,
-        c => c >= (48 as char) && c <= (57 as char),
+/// create the error for a byte `c` at the cursor that can't be part of a number
+fn invalid_number_char(state: &ParserState, c: u8, reason: &str) -> FuzJsonParseError {
+    let found = if c.is_ascii() {
+        c as char
+    } else {
+        state.peek_char().unwrap_or(char::REPLACEMENT_CHARACTER)
+    };
+    state.error(
+        ErrorKind::InvalidNumber,
+        format!("invalid number, {reason} found `{}`", found.escape_debug()),
+    )
+}
+
+/// move the cursor to the next `,`, `]` or `}` that is not nested in an array, object or string
+///
+/// used in recovery mode to skip the rest of a value that failed to parse,
+/// stops at the end of the string if there is no such character
+pub fn skip_to_sync_point(state: &mut ParserState) {
+    let mut depth = 0usize;
+    while let Some(c) = peek(state) {
+        match c {
+            b',' | b']' | b'}' if depth == 0 => return,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => depth -= 1,
+            b'"' => {
+                state.pos += 1;
+                while let Some(c) = advance(state) {
+                    match c {
+                        b'"' => break,
+                        b'\\' => state.skip(1),
+                        _ => (),
+                    }
+                }
+                continue;
+            }
+            _ => (),
+        }
+        state.pos += 1;
     }
 }
 
 /// move cursor t next character that is not whitespace
+#[inline]
 pub fn consume_whitespace(state: &mut ParserState) {
-    while is_whitespace(state) {
-        advance(state);
+    const SPACES: u64 = u64::from_ne_bytes([b' '; 8]);
+    let bytes = state.remaining();
+    let mut count = 0;
+    loop {
+        match bytes.get(count) {
+            Some(b' ') => match bytes.get(count..count + 8) {
+                // indentation comes in runs of spaces, skip up to eight of them at once
+                Some(chunk) => {
+                    let not_spaces = u64::from_le_bytes(chunk.try_into().unwrap()) ^ SPACES;
+                    count += not_spaces.trailing_zeros() as usize / 8;
+                }
+                None => count += 1,
+            },
+            Some(b'\t' | b'\n' | b'\r') => count += 1,
+            _ => break,
+        }
     }
+    state.pos += count;
 }
 
 /// check if the character at the cursor is white space used by [consume_whitespace]
-pub fn is_whitespace(state: &mut ParserState) -> bool {
-    matches!(peek(state), Some(' ' | '\t' | '\n'))
+#[inline]
+pub fn is_whitespace(state: &ParserState) -> bool {
+    matches!(peek(state), Some(b' ' | b'\t' | b'\n' | b'\r'))
 }
@@ -1,8 +1,31 @@
+mod convert;
+#[cfg(feature = "serde")]
+pub mod de;
+mod diagnostic;
 pub mod error;
 pub mod json_parser;
+mod macros;
+pub mod map;
+mod traits;
+pub mod value_ref;
+#[cfg(feature = "serde")]
+mod value_serde;
 pub mod values;
 
 pub use json_parser::parse as json_parse;
+pub use traits::{FromJson, ToJson};
+
+#[cfg(feature = "serde")]
+pub use de::{from_str, from_str_with_options};
+
+#[cfg(feature = "derive")]
+pub use fuz_json_derive::{FromJson, ToJson};
+
+// lets the code from `fuz_json_derive` name this crate from inside it too
+extern crate self as fuz_json_parser;
+
+#[doc(hidden)]
+pub use traits::derive_support as __private;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,136 @@
+/// build a [JsonValue](crate::values::JsonValue) from JSON syntax
+///
+/// any value can be a Rust expression instead, it is converted with `JsonValue::from`.
+/// Object keys are string literals or any single token that converts into a `String`,
+/// like a variable or an expression in parentheses
+/// ```
+/// use fuz_json_parser::{json, json_parse};
+///
+/// let name = "fuz";
+/// let versions = 3;
+/// let value = json!({
+///     "name": name,
+///     "versions": versions,
+///     "latest": [0, 1, versions - 1],
+///     "meta": {"license": "MIT", "yanked": false, "docs": null},
+///     (format!("{name}_id")): 1.5,
+/// });
+/// assert_eq!(
+///     value,
+///     json_parse(r#"{
+///         "name": "fuz", "versions": 3, "latest": [0, 1, 2], "fuz_id": 1.5,
+///         "meta": {"license": "MIT", "yanked": false, "docs": null}
+///     }"#)
+///     .unwrap()
+/// );
+/// ```
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+/// the rules behind [json!], not meant to be used on its own
+///
+/// arrays and objects are read one element at a time. `null`, `true`, `false`, arrays and
+/// objects are matched as tokens first since they are not Rust expressions (or not the
+/// intended ones), anything else is read as an expression up to the next `,`
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // array elements, the finished elements are collected in the brackets
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($last)])
+    };
+    // the comma after an element matched as tokens
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // object members, each one is inserted into `$map` as soon as it is read
+    (@object $map:ident) => {};
+    (@object $map:ident $key:tt : null $($rest:tt)*) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!(null));
+        $crate::json_internal!(@object_comma $map $($rest)*);
+    };
+    (@object $map:ident $key:tt : true $($rest:tt)*) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!(true));
+        $crate::json_internal!(@object_comma $map $($rest)*);
+    };
+    (@object $map:ident $key:tt : false $($rest:tt)*) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!(false));
+        $crate::json_internal!(@object_comma $map $($rest)*);
+    };
+    (@object $map:ident $key:tt : [$($array:tt)*] $($rest:tt)*) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!([$($array)*]));
+        $crate::json_internal!(@object_comma $map $($rest)*);
+    };
+    (@object $map:ident $key:tt : {$($object:tt)*} $($rest:tt)*) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!({$($object)*}));
+        $crate::json_internal!(@object_comma $map $($rest)*);
+    };
+    (@object $map:ident $key:tt : $value:expr, $($rest:tt)*) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!($value));
+        $crate::json_internal!(@object $map $($rest)*);
+    };
+    (@object $map:ident $key:tt : $value:expr) => {
+        $map.insert(::std::string::String::from($key), $crate::json_internal!($value));
+    };
+    // the comma after a value matched as tokens
+    (@object_comma $map:ident) => {};
+    (@object_comma $map:ident , $($rest:tt)*) => {
+        $crate::json_internal!(@object $map $($rest)*);
+    };
+
+    (null) => {
+        $crate::values::JsonValue::Null
+    };
+    (true) => {
+        $crate::values::JsonValue::Bool(true)
+    };
+    (false) => {
+        $crate::values::JsonValue::Bool(false)
+    };
+    ([]) => {
+        $crate::values::JsonValue::Array(vec![])
+    };
+    ([$($array:tt)+]) => {
+        $crate::values::JsonValue::Array($crate::json_internal!(@array [] $($array)+))
+    };
+    ({}) => {
+        $crate::values::JsonValue::Obj($crate::values::Map::new())
+    };
+    ({$($object:tt)+}) => {{
+        let mut map = $crate::values::Map::new();
+        $crate::json_internal!(@object map $($object)+);
+        $crate::values::JsonValue::Obj(map)
+    }};
+    ($other:expr) => {
+        $crate::values::JsonValue::from($other)
+    };
+}
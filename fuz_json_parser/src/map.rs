@@ -0,0 +1,314 @@
+//! the map that holds the members of a [JsonValue::Obj](crate::values::JsonValue::Obj)
+//!
+//! members are kept in hash order by default and in the order they were parsed or inserted
+//! with the `preserve_order` feature. The types and methods here are the same with and
+//! without the feature, so one crate turning it on can't break another crate using this one
+
+use std::{
+    borrow::Borrow,
+    fmt::Debug,
+    hash::Hash,
+    iter::FusedIterator,
+    ops::{Index, IndexMut},
+};
+
+use crate::values::JsonValue;
+
+#[cfg(not(feature = "preserve_order"))]
+type MapImpl<K, V> = std::collections::HashMap<K, V>;
+#[cfg(feature = "preserve_order")]
+type MapImpl<K, V> = indexmap::IndexMap<K, V>;
+
+/// the members of an object, a `HashMap` by default and an `IndexMap` with the
+/// `preserve_order` feature
+#[derive(Clone)]
+pub struct Map<K = String, V = JsonValue> {
+    // boxed so an object takes no more room in a value than a string does,
+    // which keeps `JsonValue` at 32 bytes
+    map: Box<MapImpl<K, V>>,
+}
+
+impl<K, V> Map<K, V> {
+    /// create an empty map
+    pub fn new() -> Self {
+        Map {
+            map: Box::default(),
+        }
+    }
+
+    /// create an empty map with room for `capacity` members
+    pub fn with_capacity(capacity: usize) -> Self {
+        Map {
+            map: Box::new(MapImpl::with_capacity_and_hasher(
+                capacity,
+                Default::default(),
+            )),
+        }
+    }
+
+    /// the number of members
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// check if there are no members
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// remove every member
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// the members in the order of the map
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.map.iter())
+    }
+
+    /// the members with values that can be changed
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.map.iter_mut())
+    }
+
+    /// the keys of the members
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.map.keys())
+    }
+
+    /// the values of the members
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.map.values())
+    }
+
+    /// the values of the members that can be changed
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.map.values_mut())
+    }
+}
+
+impl<K: Hash + Eq, V> Map<K, V> {
+    /// the value of the member `key`
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get(key)
+    }
+
+    /// the value of the member `key` that can be changed
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// check if there is a member `key`
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// add a member and return the value it replaced, a replaced member keeps its place
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.map.insert(key, value)
+    }
+
+    /// remove the member `key` and return its value, the other members keep their order
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.map.remove(key);
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove(key);
+    }
+
+    /// the member `key` to insert or change in place
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        Entry(self.map.entry(key))
+    }
+
+    /// keep only the members `keep` returns `true` for
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, keep: F) {
+        self.map.retain(keep)
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Map::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for Map<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+/// maps are equal when they have the same members, whatever their order
+impl<K: Hash + Eq, V: PartialEq> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K: Hash + Eq, V: Eq> Eq for Map<K, V> {}
+
+/// the value of the member `key`
+///
+/// # Panics
+/// if there is no member `key`
+impl<K: Hash + Eq + Borrow<Q>, V, Q: Hash + Eq + ?Sized> Index<&Q> for Map<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no member with that key")
+    }
+}
+
+/// the value of the member `key` that can be changed
+///
+/// # Panics
+/// if there is no member `key`
+impl<K: Hash + Eq + Borrow<Q>, V, Q: Hash + Eq + ?Sized> IndexMut<&Q> for Map<K, V> {
+    fn index_mut(&mut self, key: &Q) -> &mut V {
+        self.get_mut(key).expect("no member with that key")
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Map {
+            map: Box::new(MapImpl::from_iter(iter)),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for Map<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.map.extend(iter)
+    }
+}
+
+impl<K, V> IntoIterator for Map<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter((*self.map).into_iter())
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Map<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut Map<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+type EntryImpl<'a, K, V> = std::collections::hash_map::Entry<'a, K, V>;
+#[cfg(feature = "preserve_order")]
+type EntryImpl<'a, K, V> = indexmap::map::Entry<'a, K, V>;
+
+/// a member of a [Map] that may or may not exist yet, returned by [Map::entry]
+/// and [JsonValue::entry]
+pub struct Entry<'a, K = String, V = JsonValue>(EntryImpl<'a, K, V>);
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// the key of the member
+    pub fn key(&self) -> &K {
+        self.0.key()
+    }
+
+    /// the value of the member, inserting `default` if it doesn't exist
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.0.or_insert(default)
+    }
+
+    /// the value of the member, inserting the result of `default` if it doesn't exist
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.0.or_insert_with(default)
+    }
+
+    /// change the value of the member if it exists
+    pub fn and_modify<F: FnOnce(&mut V)>(self, modify: F) -> Self {
+        Entry(self.0.and_modify(modify))
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    /// the value of the member, inserting the default value if it doesn't exist
+    pub fn or_default(self) -> &'a mut V {
+        self.0.or_default()
+    }
+}
+
+/// wrap the iterator types of the map behind the feature so they are the same either way
+macro_rules! map_iterator {
+    ($(#[$doc:meta])* $name:ident<$($lt:lifetime,)? K, V> => $item:ty) => {
+        $(#[$doc])*
+        pub struct $name<$($lt,)? K, V>(
+            #[cfg(not(feature = "preserve_order"))] std::collections::hash_map::$name<$($lt,)? K, V>,
+            #[cfg(feature = "preserve_order")] indexmap::map::$name<$($lt,)? K, V>,
+        );
+
+        impl<$($lt,)? K, V> Iterator for $name<$($lt,)? K, V> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<$($lt,)? K, V> ExactSizeIterator for $name<$($lt,)? K, V> {}
+
+        impl<$($lt,)? K, V> FusedIterator for $name<$($lt,)? K, V> {}
+    };
+}
+
+map_iterator!(
+    /// the members of a [Map], from [Map::iter]
+    Iter<'a, K, V> => (&'a K, &'a V)
+);
+map_iterator!(
+    /// the members of a [Map] with values that can be changed, from [Map::iter_mut]
+    IterMut<'a, K, V> => (&'a K, &'a mut V)
+);
+map_iterator!(
+    /// the members of a [Map] moved out of it
+    IntoIter<K, V> => (K, V)
+);
+map_iterator!(
+    /// the keys of a [Map], from [Map::keys]
+    Keys<'a, K, V> => &'a K
+);
+map_iterator!(
+    /// the values of a [Map], from [Map::values]
+    Values<'a, K, V> => &'a V
+);
+map_iterator!(
+    /// the values of a [Map] that can be changed, from [Map::values_mut]
+    ValuesMut<'a, K, V> => &'a mut V
+);
@@ -1,21 +1,21 @@
+use crate::error::ErrorKind;
 pub use crate::json_parse;
+use crate::json_parser::{parse_partial, parse_recovering, parse_with_options, ParseOptions};
 use crate::values::JsonValue::{Array, Bool, Null, Num, Obj};
-use crate::values::{JsonNum::*, JsonValue};
-use std::collections::HashMap;
+use crate::values::{JsonNum::*, JsonValue, Map};
 
 macro_rules! collection {
     // map-like
     ($($k:expr => $v:expr),* $(,)?) => {{
-        use std::{collections::HashMap, iter::FromIterator};
-        HashMap::<_,_>::from_iter(([$(($k, $v),)*]).into_iter())
+        use std::iter::FromIterator;
+        $crate::values::Map::<_,_>::from_iter(([$(($k, $v),)*]).into_iter())
     }};
 }
 
 #[test]
 fn basic_value_types() {
     assert_eq!(json_parse("[]"), Ok(Array(vec![])));
-    assert_eq!(json_parse("{}"), Ok(Obj(HashMap::new())));
-    assert_eq!(json_parse("    "), Ok(Null));
+    assert_eq!(json_parse("{}"), Ok(Obj(Map::new())));
     assert_eq!(json_parse("1"), Ok(Num(Int(1))));
     assert_eq!(json_parse("1.1"), Ok(Num(Float(1.1))));
     assert_eq!(json_parse("null"), Ok(Null));
@@ -31,13 +31,201 @@ fn basic_value_types() {
 fn bools() {
     assert_eq!(json_parse("true"), Ok(Bool(true)));
     assert_eq!(json_parse("false"), Ok(Bool(false)));
-    assert_eq!(json_parse("False"), Ok(Bool(false)));
-    assert_eq!(json_parse("TRUE"), Ok(Bool(true)));
-    assert_eq!(json_parse("fAlSE"), Ok(Bool(false)));
+    assert!(json_parse("False").is_err());
+    assert!(json_parse("TRUE").is_err());
+
+    let lenient = ParseOptions::lenient();
+    assert_eq!(parse_with_options("False", &lenient), Ok(Bool(false)));
+    assert_eq!(parse_with_options("TRUE", &lenient), Ok(Bool(true)));
+    assert_eq!(parse_with_options("fAlSE", &lenient), Ok(Bool(false)));
+}
+
+mod options {
+    pub use super::*;
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(json_parse("").unwrap_err().kind(), ErrorKind::EmptyInput);
+        assert_eq!(
+            json_parse(" \n\t ").unwrap_err().kind(),
+            ErrorKind::EmptyInput
+        );
+        assert_eq!(
+            parse_partial("  ").unwrap_err().kind(),
+            ErrorKind::EmptyInput
+        );
+        assert_eq!(
+            json_parse("[").unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+
+        let options = ParseOptions {
+            allow_empty_input: true,
+            ..ParseOptions::strict()
+        };
+        assert_eq!(parse_with_options("    ", &options), Ok(Null));
+        assert_eq!(parse_with_options("", &options), Ok(Null));
+        assert_eq!(parse_with_options(" null ", &options), Ok(Null));
+        assert!(parse_with_options("[", &options).is_err());
+    }
+
+    #[test]
+    fn default_is_strict() {
+        assert_eq!(ParseOptions::default(), ParseOptions::strict());
+    }
+
+    #[test]
+    fn carriage_return_is_whitespace() {
+        assert_eq!(
+            json_parse("\r\n[1,\r\n2]\r\n"),
+            Ok(Array(vec![Num(Int(1)), Num(Int(2))]))
+        );
+    }
+
+    #[test]
+    fn case_insensitive_literals() {
+        let options = ParseOptions {
+            case_insensitive_literals: true,
+            ..ParseOptions::strict()
+        };
+        assert_eq!(parse_with_options("NULL", &options), Ok(Null));
+        assert_eq!(
+            parse_with_options("[True]", &options),
+            Ok(Array(vec![Bool(true)]))
+        );
+        assert!(parse_with_options(".5", &options).is_err());
+
+        assert!(json_parse("NULL").is_err());
+        assert!(json_parse("nUll").is_err());
+        assert!(json_parse("[True]").is_err());
+    }
+
+    #[test]
+    fn leading_decimal_point() {
+        let options = ParseOptions {
+            allow_leading_decimal_point: true,
+            ..ParseOptions::strict()
+        };
+        assert_eq!(parse_with_options(".5", &options), Ok(Num(Float(0.5))));
+        assert_eq!(parse_with_options("-.5e1", &options), Ok(Num(Float(-5.0))));
+        assert!(parse_with_options(".", &options).is_err());
+        assert!(parse_with_options(".e1", &options).is_err());
+        assert!(parse_with_options("TRUE", &options).is_err());
+
+        assert!(json_parse(".5").is_err());
+        assert!(json_parse("-.5").is_err());
+    }
+}
+
+mod numbers {
+    pub use super::*;
+
+    #[test]
+    fn integers() {
+        assert_eq!(json_parse("0"), Ok(Num(Int(0))));
+        assert_eq!(json_parse("-0"), Ok(Num(Int(0))));
+        assert_eq!(json_parse("1234567890"), Ok(Num(Int(1234567890))));
+        assert_eq!(json_parse("-17"), Ok(Num(Int(-17))));
+    }
+
+    #[test]
+    fn fractions_and_exponents() {
+        assert_eq!(json_parse("0.5"), Ok(Num(Float(0.5))));
+        assert_eq!(json_parse("1e10"), Ok(Num(Float(1e10))));
+        assert_eq!(json_parse("2.5E-3"), Ok(Num(Float(2.5e-3))));
+        assert_eq!(json_parse("-0.0e+1"), Ok(Num(Float(-0.0))));
+        assert_eq!(json_parse("1E+2"), Ok(Num(Float(100.0))));
+        assert_eq!(
+            json_parse("[1e1,2]"),
+            Ok(Array(vec![Num(Float(10.0)), Num(Int(2))]))
+        );
+    }
+
+    #[test]
+    fn same_as_str_parse() {
+        let floats = [
+            "0.1",
+            "-0.0",
+            "0.30000000000000004",
+            "123456789.123456789",
+            "-9007199254740993.5",
+            "0.000000000000000001",
+            "12345678.12345678",
+            "1234567890123456.78",
+            "-3.141592653589793238462643383279",
+            "4.9406564584124654",
+            "179769313486231570000000000000000000000.0",
+        ];
+        for text in floats {
+            let expected: f64 = text.parse().unwrap();
+            match json_parse(text) {
+                Ok(Num(Float(float))) => assert_eq!(float.to_bits(), expected.to_bits(), "{text}"),
+                other => panic!("{text} parsed as {other:?}"),
+            }
+        }
+
+        let ints = [
+            "999999999999999999",
+            "-999999999999999999",
+            "1000000000000000000",
+            "-9223372036854775808",
+            "12345678",
+            "123456789",
+        ];
+        for text in ints {
+            let expected: i128 = text.parse().unwrap();
+            assert_eq!(json_parse(text), Ok(Num(Int(expected))), "{text}");
+        }
+        assert_eq!(
+            json_parse("[12345678901234567.5,-1]"),
+            Ok(Array(vec![Num(Float(12345678901234567.5)), Num(Int(-1))]))
+        );
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        assert!(json_parse(".5").is_err());
+        assert!(json_parse("--1").is_err());
+        assert!(json_parse("-").is_err());
+        assert!(json_parse("01").is_err());
+        assert!(json_parse("-012").is_err());
+        assert!(json_parse("1.").is_err());
+        assert!(json_parse("1.e5").is_err());
+        assert!(json_parse("1e").is_err());
+        assert!(json_parse("1e+").is_err());
+        assert!(json_parse("-a").is_err());
+        assert!(json_parse("[1-2.3.4]").is_err());
+        assert!(json_parse("[1.2.3]").is_err());
+        assert!(json_parse("1e400").is_err());
+    }
+
+    #[test]
+    fn error_position() {
+        let e = json_parse("[-012]").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+        assert_eq!(e.offset(), 3);
+        assert!(e.message().contains("`1`"), "{e}");
+
+        let e = json_parse("1.x").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+        assert_eq!(e.offset(), 2);
+        assert!(e.message().contains("`x`"), "{e}");
+    }
 }
 
 mod map {
     use super::*;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn boxed_map_keeps_values_small() {
+        assert_eq!(std::mem::size_of::<JsonValue>(), 32);
+        assert_eq!(
+            std::mem::size_of::<crate::value_ref::JsonValueRef<'_>>(),
+            32
+        );
+    }
+
     #[test]
     fn map1() {
         assert_eq!(
@@ -60,6 +248,29 @@ mod map {
         ])
     ));
     }
+
+    #[test]
+    fn same_api_with_any_features() {
+        let mut map: Map = Map::new();
+        map.insert("a".to_string(), Num(Int(1)));
+        map.insert("b".to_string(), Null);
+        *map.entry("a".to_string()).or_insert(Null) = Num(Int(2));
+        map.entry("c".to_string())
+            .and_modify(|_| panic!("c doesn't exist yet"))
+            .or_default();
+        assert_eq!(map["a"], Num(Int(2)));
+        assert_eq!(map.remove("b"), Some(Null));
+        assert!(!map.contains_key("b"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.iter().count(), 2);
+
+        // equal maps have the same members whatever their order
+        let mut members: Vec<_> = map.clone().into_iter().collect();
+        members.reverse();
+        assert_eq!(members.into_iter().collect::<Map>(), map);
+        let value = Obj(map);
+        assert_eq!(value.as_object().map(|o| o.keys().count()), Some(2));
+    }
 }
 
 #[test]
@@ -146,6 +357,1927 @@ mod edge_cases {
         assert_eq!(json_parse("[          ]"), Ok(Array(vec![])));
     }
 }
+mod escapes {
+    pub use super::*;
+
+    #[test]
+    fn all_escapes() {
+        assert_eq!(
+            json_parse(r#""\"\\\/\b\f\n\r\t""#),
+            Ok(JsonValue::String("\"\\/\u{8}\u{c}\n\r\t".into()))
+        );
+    }
+
+    #[test]
+    fn escaped_slash() {
+        assert_eq!(
+            json_parse(r#"["http:\/\/example.com\/"]"#),
+            Ok(Array(vec![JsonValue::String("http://example.com/".into())]))
+        );
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        assert!(json_parse(r#""\a""#).is_err());
+        assert!(json_parse(r#""\'""#).is_err());
+        assert!(json_parse(r#""\x41""#).is_err());
+        assert!(json_parse(r#""\"#).is_err());
+    }
+
+    #[test]
+    fn raw_control_characters() {
+        assert!(json_parse("\"a\nb\"").is_err());
+        assert!(json_parse("\"a\tb\"").is_err());
+        assert!(json_parse("\"\u{0}\"").is_err());
+        assert!(json_parse("\"\u{1f}\"").is_err());
+        assert!(json_parse("{\"a\rb\": 1}").is_err());
+        assert_eq!(
+            json_parse("\"\u{7f}\u{20}\""),
+            Ok(JsonValue::String("\u{7f} ".into()))
+        );
+    }
+}
+
+mod unicode_escapes {
+    pub use super::*;
+
+    #[test]
+    fn basic_multilingual_plane() {
+        assert_eq!(
+            json_parse(r#""\u0041\u00e9\u4E2D""#),
+            Ok(JsonValue::String("Aé中".into()))
+        );
+    }
+
+    #[test]
+    fn surrogate_pair() {
+        assert_eq!(
+            json_parse(r#""\uD83D\uDE00 \ud834\udd1e""#),
+            Ok(JsonValue::String("😀 𝄞".into()))
+        );
+    }
+
+    #[test]
+    fn lone_high_surrogate() {
+        assert!(json_parse(r#""\uD83D""#).is_err());
+        assert!(json_parse(r#""\uD83Dabc""#).is_err());
+        assert!(json_parse(r#""\uD83D\n""#).is_err());
+        assert!(json_parse(r#""\uD83D\u0041""#).is_err());
+    }
+
+    #[test]
+    fn lone_low_surrogate() {
+        assert!(json_parse(r#""\uDE00""#).is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(json_parse(r#""\u12""#).is_err());
+        assert!(json_parse(r#""\u12G4""#).is_err());
+        assert!(json_parse(r#""\u"#).is_err());
+    }
+}
+
+mod encode {
+    pub use super::*;
+
+    /// every value here must come back unchanged from both `encode` and `Display`
+    fn round_trip_values() -> Vec<JsonValue> {
+        let every_ascii: String = (0u8..0x80).map(char::from).collect();
+        vec![
+            Null,
+            Bool(true),
+            Num(Int(-42)),
+            Num(Float(1.0)),
+            Num(Float(-0.25)),
+            Num(Float(1e300)),
+            JsonValue::String(every_ascii.clone()),
+            JsonValue::String("quote \" backslash \\ newline \n".into()),
+            JsonValue::String("\u{8}\u{c}\u{0}\u{1f} é 中 😀".into()),
+            Array(vec![
+                JsonValue::String("\"".into()),
+                JsonValue::String("\\".into()),
+                Array(vec![Null]),
+            ]),
+            Obj(collection![
+                "\"key\"".to_string() => JsonValue::String("\r\t".into()),
+                "back\\slash".to_string() => Obj(collection![
+                    "new\nline".to_string() => Num(Int(1)),
+                ]),
+                every_ascii => Array(vec![]),
+            ]),
+            Array(
+                (0..12)
+                    .map(|i| JsonValue::String(format!("\"{i}\"")))
+                    .collect(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(
+            JsonValue::String("a\"b\\c\nd\u{1}".into()).encode(),
+            r#""a\"b\\c\nd\u0001""#
+        );
+        assert_eq!(
+            JsonValue::String("a\"b\\c\nd\u{1}".into()).to_string(),
+            r#""a\"b\\c\nd\u0001""#
+        );
+        assert_eq!(
+            Obj(collection!["\"".to_string() => Null]).encode(),
+            r#"{"\"":null}"#
+        );
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        for value in round_trip_values() {
+            assert_eq!(json_parse(value.encode()), Ok(value));
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for value in round_trip_values() {
+            assert_eq!(json_parse(value.to_string()), Ok(value));
+        }
+    }
+
+    #[test]
+    fn streaming_matches_encode() {
+        for value in round_trip_values() {
+            let mut bytes = Vec::new();
+            value.write_to(&mut bytes).unwrap();
+            assert_eq!(String::from_utf8(bytes).unwrap(), value.encode());
+
+            let mut text = String::from("prefix ");
+            value.write_to_fmt(&mut text).unwrap();
+            assert_eq!(text, format!("prefix {}", value.encode()));
+
+            let source = value.encode();
+            let borrowed = crate::json_parser::parse_borrowed(&source).unwrap();
+            let mut bytes = Vec::new();
+            borrowed.write_to(&mut bytes).unwrap();
+            assert_eq!(String::from_utf8(bytes).unwrap(), borrowed.encode());
+        }
+    }
+
+    #[test]
+    fn streaming_io_error() {
+        /// accepts `room` bytes and then fails
+        struct Full {
+            room: usize,
+        }
+        impl std::io::Write for Full {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if buf.len() > self.room {
+                    return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "full"));
+                }
+                self.room -= buf.len();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value = Array((0..100).map(|i| Num(Int(i))).collect());
+        let e = value.write_to(Full { room: 50 }).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::WriteZero);
+        assert!(value.write_to(Full { room: 1000 }).is_ok());
+    }
+}
+
+mod trailing_content {
+    pub use super::*;
+
+    #[test]
+    fn trailing_content_is_error() {
+        assert!(json_parse("{} garbage").is_err());
+        assert!(json_parse("1 2").is_err());
+        assert!(json_parse("[1]]").is_err());
+        assert!(json_parse("\"a\"\"b\"").is_err());
+        assert!(json_parse("true false").is_err());
+        assert!(json_parse("1-2.3.4").is_err());
+    }
+
+    #[test]
+    fn trailing_whitespace_is_allowed() {
+        assert_eq!(json_parse(" {} \n\t\r "), Ok(Obj(Map::new())));
+    }
+
+    #[test]
+    fn partial() {
+        assert_eq!(parse_partial("1 2"), Ok((Num(Int(1)), 1)));
+        assert_eq!(parse_partial("  [] garbage"), Ok((Array(vec![]), 4)));
+        assert_eq!(
+            parse_partial("\"é\"x"),
+            Ok((JsonValue::String("é".into()), 4))
+        );
+        assert!(parse_partial("[1,]").is_err());
+    }
+
+    #[test]
+    fn concatenated_values() {
+        let json_str = "{\"a\":1}\n[2]\n\"三\"\n4";
+        let mut values = vec![];
+        let mut offset = 0;
+        while !json_str[offset..].trim().is_empty() {
+            let (value, end) = parse_partial(&json_str[offset..]).unwrap();
+            values.push(value);
+            offset += end;
+        }
+        assert_eq!(
+            values,
+            vec![
+                Obj(collection!["a".to_string() => Num(Int(1))]),
+                Array(vec![Num(Int(2))]),
+                JsonValue::String("三".into()),
+                Num(Int(4)),
+            ]
+        );
+    }
+}
+
+mod errors {
+    pub use super::*;
+
+    fn kind(json_str: &str) -> ErrorKind {
+        json_parse(json_str).unwrap_err().kind()
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(kind(""), ErrorKind::EmptyInput);
+        assert_eq!(kind("[1,"), ErrorKind::UnexpectedEof);
+        assert_eq!(kind("\"abc"), ErrorKind::UnexpectedEof);
+        assert_eq!(kind("tru"), ErrorKind::UnexpectedEof);
+        assert_eq!(kind("[1 2]"), ErrorKind::UnexpectedChar);
+        assert_eq!(kind("{\"a\" 1}"), ErrorKind::UnexpectedChar);
+        assert_eq!(kind("trux"), ErrorKind::UnexpectedChar);
+        assert_eq!(kind("\"\\q\""), ErrorKind::InvalidEscape);
+        assert_eq!(kind("\"\\u12x4\""), ErrorKind::InvalidEscape);
+        assert_eq!(kind("\"\\uDE00\""), ErrorKind::InvalidEscape);
+        assert_eq!(kind("01"), ErrorKind::InvalidNumber);
+        assert_eq!(kind("1e999"), ErrorKind::InvalidNumber);
+        assert_eq!(kind("\"a\nb\""), ErrorKind::ControlCharacter);
+        assert_eq!(kind("{} {}"), ErrorKind::TrailingCharacters);
+    }
+
+    #[test]
+    fn line_and_column() {
+        let e = json_parse("{\n  \"a\": 1,\n  \"b\": x\n}").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedChar);
+        assert_eq!((e.line(), e.column(), e.offset()), (3, 8, 19));
+        assert_eq!(e.to_string(), "expected value found `x` at line 3 column 8");
+
+        let e = json_parse("[1,\r\n 2,").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!((e.line(), e.column(), e.offset()), (2, 4, 8));
+    }
+
+    #[test]
+    fn column_counts_characters() {
+        let e = json_parse("[\"é中😀\", ?]").unwrap_err();
+        assert_eq!(e.column(), 9);
+        assert_eq!(e.offset(), 14);
+    }
+
+    #[test]
+    fn escape_position() {
+        let e = json_parse("[\"ab\\uD83Dcd\"]").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidEscape);
+        assert_eq!(e.offset(), 4);
+
+        let e = json_parse("\"ab\\x\"").unwrap_err();
+        assert_eq!(e.offset(), 4);
+    }
+}
+
+mod diagnostic {
+    pub use super::*;
+
+    fn render(source: &str) -> String {
+        json_parse(source).unwrap_err().render(source)
+    }
+
+    #[test]
+    fn snippet_and_caret() {
+        assert_eq!(
+            render("[1,\n\t2 3]"),
+            "error: expected either `,` or `]` found `3`
+ --> line 2, column 4
+  |
+2 | \t2 3]
+  | \t  ^
+  = note: a character appeared where it is not allowed
+"
+        );
+    }
+
+    #[test]
+    fn hints() {
+        assert!(render("[1, 2, ]").contains("hint: JSON does not allow a trailing comma"));
+        assert!(render("{'a': 1}").contains("hint: JSON strings and keys must use double quotes"));
+        assert!(render("[1, 'a']").contains("hint: JSON strings and keys must use double quotes"));
+        assert!(render("{\"a\": 1, b_2 : 2}")
+            .contains("hint: object keys must be strings, write `\"b_2\"` instead"));
+        assert!(!render("[1, b]").contains("hint"));
+        assert!(!render("[1 2]").contains("hint"));
+    }
+
+    #[test]
+    fn end_of_input() {
+        let rendered = render("{\"a\": [1,\n");
+        assert!(rendered.contains("--> line 2, column 1"), "{rendered}");
+        assert!(rendered.contains("found end of input"), "{rendered}");
+    }
+
+    #[test]
+    fn ansi() {
+        let source = "[1,]";
+        let error = json_parse(source).unwrap_err();
+        let rendered = error.render_ansi(source);
+        assert!(rendered.contains("\x1b[1;31merror\x1b[0m"));
+        assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+        assert!(!error.render(source).contains('\x1b'));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn without_position() {
+        let error = <crate::error::FuzJsonParseError as serde::de::Error>::custom("3 is odd");
+        assert_eq!(
+            error.render("3"),
+            "error: 3 is odd
+  = note: the value doesn't match the type it is read into
+"
+        );
+        assert!(error.render_ansi("").contains("3 is odd"));
+    }
+}
+
+mod recovery {
+    pub use super::*;
+
+    fn error_kinds(json_str: &str) -> Vec<ErrorKind> {
+        parse_recovering(json_str)
+            .1
+            .iter()
+            .map(|e| e.kind())
+            .collect()
+    }
+
+    #[test]
+    fn valid_input_has_no_errors() {
+        let json_str = "{\"a\": [1, 2.5, \"x\"], \"b\": {\"c\": null}}";
+        assert_eq!(
+            parse_recovering(json_str),
+            (json_parse(json_str).unwrap(), vec![])
+        );
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let (value, errors) = parse_recovering("[1, tru, 3, 01, \"ok\", x]");
+        assert_eq!(
+            value,
+            Array(vec![
+                Num(Int(1)),
+                Num(Int(3)),
+                JsonValue::String("ok".into())
+            ])
+        );
+        assert_eq!(
+            errors.iter().map(|e| e.offset()).collect::<Vec<_>>(),
+            vec![7, 13, 22]
+        );
+    }
+
+    #[test]
+    fn objects() {
+        let (value, errors) = parse_recovering("{\"a\": 1, b: 2, \"c\" 3, \"d\": [1,], \"e\": 5}");
+        assert_eq!(
+            value,
+            Obj(collection![
+                "a".to_string() => Num(Int(1)),
+                "d".to_string() => Array(vec![Num(Int(1))]),
+                "e".to_string() => Num(Int(5)),
+            ])
+        );
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].column(), 10);
+    }
+
+    #[test]
+    fn positions_of_many_errors() {
+        let json_str = "[\n  x, \"é\", y,\n\n  1, z, 2,\n  [\"ü\", w], v\n]";
+        let (_, errors) = parse_recovering(json_str);
+        assert_eq!(errors.len(), 5);
+        for e in &errors {
+            let fresh = crate::error::FuzJsonParseError::new(e.kind(), "", json_str, e.offset());
+            assert_eq!((e.line(), e.column()), (fresh.line(), fresh.column()));
+        }
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.line(), e.column()))
+                .collect::<Vec<_>>(),
+            vec![(2, 3), (2, 11), (4, 6), (5, 9), (5, 13)]
+        );
+
+        // every error only looks at the text since the one before
+        let long = format!("[{}1]", "x,".repeat(100_000));
+        let (_, errors) = parse_recovering(&long);
+        assert_eq!(errors.len(), 100_000);
+        assert_eq!(errors.last().unwrap().column(), 200_000);
+    }
+
+    #[test]
+    fn string_errors_keep_the_string() {
+        let (value, errors) = parse_recovering("[\"a\\qb\", \"c\td\"]");
+        assert_eq!(
+            value,
+            Array(vec![
+                JsonValue::String("aqb".into()),
+                JsonValue::String("c\td".into())
+            ])
+        );
+        assert_eq!(
+            errors.iter().map(|e| e.kind()).collect::<Vec<_>>(),
+            vec![ErrorKind::InvalidEscape, ErrorKind::ControlCharacter]
+        );
+    }
+
+    #[test]
+    fn nested_values_are_skipped() {
+        let (value, errors) = parse_recovering("[[1, x], {\"a\": [\"]\", ?]}, 4]");
+        assert_eq!(
+            value,
+            Array(vec![
+                Array(vec![Num(Int(1))]),
+                Obj(collection!["a".to_string() => Array(vec![JsonValue::String("]".into())])]),
+                Num(Int(4)),
+            ])
+        );
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn missing_separators_and_closers() {
+        assert_eq!(error_kinds("[1 2]"), vec![ErrorKind::UnexpectedChar]);
+        assert_eq!(error_kinds("[1,"), vec![ErrorKind::UnexpectedEof]);
+        assert_eq!(error_kinds("{\"a\": 1"), vec![ErrorKind::UnexpectedEof]);
+        assert_eq!(error_kinds("[1}"), vec![ErrorKind::UnexpectedChar]);
+        assert_eq!(error_kinds("{]"), vec![ErrorKind::UnexpectedChar]);
+        assert_eq!(error_kinds("\"abc"), vec![ErrorKind::UnexpectedEof]);
+        assert_eq!(error_kinds(""), vec![ErrorKind::EmptyInput]);
+        assert_eq!(error_kinds("1 2"), vec![ErrorKind::TrailingCharacters]);
+    }
+}
+
+mod depth_limit {
+    pub use super::*;
+    use crate::json_parser::options::DEFAULT_MAX_DEPTH;
+
+    fn nested_arrays(depth: usize) -> String {
+        format!("{}{}", "[".repeat(depth), "]".repeat(depth))
+    }
+
+    fn nested_objects(depth: usize) -> String {
+        format!("{}null{}", "{\"a\":".repeat(depth), "}".repeat(depth))
+    }
+
+    #[test]
+    fn at_the_limit() {
+        assert!(json_parse(nested_arrays(DEFAULT_MAX_DEPTH)).is_ok());
+        assert!(json_parse(nested_objects(DEFAULT_MAX_DEPTH)).is_ok());
+
+        let e = json_parse(nested_arrays(DEFAULT_MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::DepthExceeded);
+        assert_eq!(e.offset(), DEFAULT_MAX_DEPTH);
+
+        let e = json_parse(nested_objects(DEFAULT_MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::DepthExceeded);
+    }
+
+    #[test]
+    fn very_deep_input() {
+        let deep = "[".repeat(500_000);
+        assert_eq!(
+            json_parse(&deep).unwrap_err().kind(),
+            ErrorKind::DepthExceeded
+        );
+
+        let deep = "{\"a\":".repeat(500_000);
+        assert_eq!(
+            json_parse(&deep).unwrap_err().kind(),
+            ErrorKind::DepthExceeded
+        );
+
+        let deep = "[{\"a\":".repeat(250_000);
+        assert_eq!(
+            json_parse(&deep).unwrap_err().kind(),
+            ErrorKind::DepthExceeded
+        );
+    }
+
+    #[test]
+    fn very_deep_input_while_recovering() {
+        let deep = nested_arrays(500_000);
+        let (_, errors) = parse_recovering(&deep);
+        assert_eq!(
+            errors.iter().map(|e| e.kind()).collect::<Vec<_>>(),
+            vec![ErrorKind::DepthExceeded]
+        );
+
+        let (value, errors) = parse_recovering(format!("[{}, 1]", nested_arrays(200_000)));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(value[1], Num(Int(1)));
+    }
+
+    #[test]
+    fn configurable() {
+        let options = ParseOptions {
+            max_depth: 2,
+            ..ParseOptions::strict()
+        };
+        assert!(parse_with_options("[[1]]", &options).is_ok());
+        assert!(parse_with_options("[{}]", &options).is_ok());
+        assert_eq!(
+            parse_with_options("[[[1]]]", &options).unwrap_err().kind(),
+            ErrorKind::DepthExceeded
+        );
+        assert_eq!(
+            parse_with_options(
+                "[]",
+                &ParseOptions {
+                    max_depth: 0,
+                    ..options
+                }
+            )
+            .unwrap_err()
+            .kind(),
+            ErrorKind::DepthExceeded
+        );
+        assert!(parse_with_options(
+            "1",
+            &ParseOptions {
+                max_depth: 0,
+                ..options
+            }
+        )
+        .is_ok());
+
+        let options = ParseOptions {
+            max_depth: 300,
+            ..ParseOptions::strict()
+        };
+        assert!(parse_with_options(nested_arrays(300), &options).is_ok());
+    }
+}
+
+mod resource_limits {
+    pub use super::*;
+
+    fn limited(json_str: &str, options: ParseOptions) -> std::result::Result<JsonValue, ErrorKind> {
+        parse_with_options(json_str, &options).map_err(|e| e.kind())
+    }
+
+    #[test]
+    fn off_by_default() {
+        let options = ParseOptions::default();
+        assert_eq!(options.max_input_len, None);
+        assert_eq!(options.max_string_len, None);
+        assert_eq!(options.max_array_len, None);
+        assert_eq!(options.max_object_members, None);
+        assert_eq!(options.max_nodes, None);
+    }
+
+    #[test]
+    fn input_len() {
+        let options = ParseOptions {
+            max_input_len: Some(5),
+            ..ParseOptions::strict()
+        };
+        assert!(limited("[1,2]", options).is_ok());
+        assert_eq!(limited("[1, 2]", options), Err(ErrorKind::InputTooLarge));
+        assert_eq!(limited("[1,2,3", options), Err(ErrorKind::InputTooLarge));
+    }
+
+    #[test]
+    fn string_len() {
+        let options = ParseOptions {
+            max_string_len: Some(4),
+            ..ParseOptions::strict()
+        };
+        assert!(limited("[\"abcd\", \"é\\n\"]", options).is_ok());
+        assert_eq!(limited("\"abcde\"", options), Err(ErrorKind::StringTooLong));
+        assert_eq!(limited("\"abcé\"", options), Err(ErrorKind::StringTooLong));
+        assert_eq!(
+            limited("{\"abcde\": 1}", options),
+            Err(ErrorKind::StringTooLong)
+        );
+
+        let e = parse_with_options("[1, \"abcdefgh", &options).unwrap_err();
+        assert_eq!((e.kind(), e.offset()), (ErrorKind::StringTooLong, 4));
+
+        // strings with escapes are copied, each piece is checked before it is added
+        assert!(limited("\"a\\nbc\"", options).is_ok());
+        assert_eq!(
+            limited("\"a\\nbcd\"", options),
+            Err(ErrorKind::StringTooLong)
+        );
+        assert_eq!(
+            limited("\"abc\\u00e9\"", options),
+            Err(ErrorKind::StringTooLong)
+        );
+        assert_eq!(
+            limited("\"abcd\\n\"", options),
+            Err(ErrorKind::StringTooLong)
+        );
+        let e = parse_with_options("[\"\\nabcdefgh", &options).unwrap_err();
+        assert_eq!((e.kind(), e.offset()), (ErrorKind::StringTooLong, 1));
+    }
+
+    #[test]
+    fn array_len() {
+        let options = ParseOptions {
+            max_array_len: Some(2),
+            ..ParseOptions::strict()
+        };
+        assert!(limited("[[1, 2], [3, 4]]", options).is_ok());
+        assert_eq!(limited("[1, 2, 3]", options), Err(ErrorKind::ArrayTooLong));
+
+        let e = parse_with_options("[1, 2, {", &options).unwrap_err();
+        assert_eq!((e.kind(), e.offset()), (ErrorKind::ArrayTooLong, 7));
+    }
+
+    #[test]
+    fn object_members() {
+        let options = ParseOptions {
+            max_object_members: Some(1),
+            ..ParseOptions::strict()
+        };
+        assert!(limited("{\"a\": {\"b\": 1}}", options).is_ok());
+        assert_eq!(
+            limited("{\"a\": 1, \"b\": 2}", options),
+            Err(ErrorKind::TooManyMembers)
+        );
+        assert_eq!(
+            limited("{\"a\": 1, \"a\": 2}", options),
+            Err(ErrorKind::TooManyMembers)
+        );
+    }
+
+    #[test]
+    fn nodes() {
+        let options = ParseOptions {
+            max_nodes: Some(5),
+            ..ParseOptions::strict()
+        };
+        assert!(limited("[1, [2], {}]", options).is_ok());
+        assert_eq!(
+            limited("[1, [2], {}, 3]", options),
+            Err(ErrorKind::TooManyNodes)
+        );
+        assert_eq!(
+            limited("{\"a\": [1, 2, 3, 4]}", options),
+            Err(ErrorKind::TooManyNodes)
+        );
+    }
+
+    #[test]
+    fn stops_recovery() {
+        let options = ParseOptions {
+            max_array_len: Some(2),
+            ..ParseOptions::strict()
+        };
+        let (value, errors) =
+            crate::json_parser::parse_recovering_with_options("[x, 1, 2, 3, y]", &options);
+        assert_eq!(value, Null);
+        assert_eq!(
+            errors.iter().map(|e| e.kind()).collect::<Vec<_>>(),
+            vec![ErrorKind::UnexpectedChar, ErrorKind::ArrayTooLong]
+        );
+    }
+}
+
+mod borrowed {
+    pub use super::*;
+    use crate::json_parser::parse_borrowed;
+    use crate::value_ref::JsonValueRef;
+    use std::borrow::Cow;
+
+    #[test]
+    fn strings_without_escapes_are_borrowed() {
+        let value = parse_borrowed("{\"key\": [\"plain\", \"tab\\there\", \"\"]}").unwrap();
+        let JsonValueRef::Obj(map) = &value else {
+            panic!("expected an object")
+        };
+        assert!(map.keys().all(|key| matches!(key, Cow::Borrowed(_))));
+        assert!(matches!(
+            value["key"][0],
+            JsonValueRef::String(Cow::Borrowed("plain"))
+        ));
+        assert!(
+            matches!(&value["key"][1], JsonValueRef::String(Cow::Owned(s)) if s == "tab\there")
+        );
+        assert_eq!(value["key"][2].unwrap_string(), "");
+    }
+
+    #[test]
+    fn same_api_as_owned() {
+        let json_str = "{\"a\": [1, 2.5, true, null], \"b\": \"x\\u00e9\"}";
+        let value = parse_borrowed(json_str).unwrap();
+        assert_eq!(value["a"][0].unwrap_int(), 1);
+        assert_eq!(value["a"][1].unwrap_float(), 2.5);
+        assert!(value["a"][2].unwrap_bool());
+        assert_eq!(value["a"][3], JsonValueRef::Null);
+        assert_eq!(value["b"].unwrap_string(), "x\u{e9}");
+        assert_eq!(value["a"]["missing"], JsonValueRef::Null);
+
+        let owned = json_parse(json_str).unwrap();
+        assert_eq!(value.to_owned(), owned);
+        // object members have no fixed order so compare what the output parses back to
+        assert_eq!(json_parse(value.encode()).unwrap(), owned);
+        assert_eq!(json_parse(value.to_string()).unwrap(), owned);
+        assert_eq!(value.into_owned(), owned);
+    }
+
+    #[test]
+    fn display_matches_owned() {
+        let json_str = "[[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10], {\"a\\n\": [\"é\", 1e3, -0.5]}]";
+        let value = parse_borrowed(json_str).unwrap();
+        let owned = json_parse(json_str).unwrap();
+        assert_eq!(value.to_string(), owned.to_string());
+        assert!(value
+            .to_string()
+            .starts_with("[[\n   0, 1, 2, 3, 4, \n   5,"));
+    }
+
+    #[test]
+    fn errors_match_owned() {
+        for json_str in ["[1, 2", "{\"a\" 1}", "\"\\x\"", "[1] 2", ""] {
+            assert_eq!(
+                parse_borrowed(json_str).unwrap_err(),
+                json_parse(json_str).unwrap_err()
+            );
+        }
+    }
+}
+
+mod duplicate_keys {
+    pub use super::*;
+    use crate::json_parser::DuplicateKeys;
+
+    fn with_policy(
+        json_str: &str,
+        duplicate_keys: DuplicateKeys,
+    ) -> crate::error::Result<JsonValue> {
+        let options = ParseOptions {
+            duplicate_keys,
+            ..ParseOptions::strict()
+        };
+        parse_with_options(json_str, &options)
+    }
+
+    const DUPLICATED: &str = "{\"a\": 1, \"b\": [2], \"a\": 3, \"b\": 4, \"a\": 5}";
+
+    #[test]
+    fn last_wins_by_default() {
+        assert_eq!(
+            ParseOptions::default().duplicate_keys,
+            DuplicateKeys::LastWins
+        );
+        assert_eq!(
+            json_parse(DUPLICATED),
+            Ok(Obj(
+                collection!("a".to_string() => Num(Int(5)), "b".to_string() => Num(Int(4)))
+            ))
+        );
+    }
+
+    #[test]
+    fn first_wins() {
+        assert_eq!(
+            with_policy(DUPLICATED, DuplicateKeys::FirstWins),
+            Ok(Obj(collection!(
+                "a".to_string() => Num(Int(1)),
+                "b".to_string() => Array(vec![Num(Int(2))])
+            )))
+        );
+    }
+
+    #[test]
+    fn collect() {
+        assert_eq!(
+            with_policy(DUPLICATED, DuplicateKeys::Collect),
+            Ok(Obj(collection!(
+                "a".to_string() => Array(vec![Num(Int(1)), Num(Int(3)), Num(Int(5))]),
+                "b".to_string() => Array(vec![Array(vec![Num(Int(2))]), Num(Int(4))])
+            )))
+        );
+        assert_eq!(
+            with_policy("{\"a\": [1], \"b\": {\"a\": 2}}", DuplicateKeys::Collect),
+            json_parse("{\"a\": [1], \"b\": {\"a\": 2}}")
+        );
+    }
+
+    #[test]
+    fn error_points_at_duplicate() {
+        let e = with_policy("{\"a\": 1,\n \"a\": 2}", DuplicateKeys::Error).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::DuplicateKey);
+        assert_eq!((e.offset(), e.line(), e.column()), (10, 2, 2));
+        assert!(e.message().contains("`a`"));
+        assert!(with_policy("{\"a\": {\"a\": 1}, \"b\": 2}", DuplicateKeys::Error).is_ok());
+    }
+
+    #[test]
+    fn recovery_keeps_first() {
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..ParseOptions::strict()
+        };
+        let (value, errors) = crate::json_parser::parse_recovering_with_options(
+            "{\"a\": 1, \"a\": 2, \"a\": 3}",
+            &options,
+        );
+        assert_eq!(value, Obj(collection!("a".to_string() => Num(Int(1)))));
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.kind() == ErrorKind::DuplicateKey));
+    }
+}
+
+mod accessors {
+    pub use super::*;
+
+    const DOC: &str = "{\"n\": null, \"b\": true, \"i\": -3, \"f\": 1.5, \"s\": \"text\", \"a\": [1, \"x\"], \"o\": {\"k\": 2}}";
+
+    #[test]
+    fn is_and_as() {
+        let value = json_parse(DOC).unwrap();
+        assert!(value.is_object() && !value.is_array());
+        assert!(value["n"].is_null());
+        assert_eq!(value["b"].as_bool(), Some(true));
+        assert_eq!(value["i"].as_i64(), Some(-3));
+        assert_eq!(value["i"].as_u64(), None);
+        assert_eq!(value["i"].as_f64(), Some(-3.0));
+        assert_eq!(value["f"].as_f64(), Some(1.5));
+        assert_eq!(value["f"].as_i64(), None);
+        assert_eq!(value["s"].as_str(), Some("text"));
+        assert_eq!(value["a"].as_array().map(Vec::len), Some(2));
+        assert_eq!(value["o"].as_object().map(|o| o.len()), Some(1));
+
+        for key in ["n", "b", "i", "f", "a", "o"] {
+            assert_eq!(value[key].as_str(), None, "{key}");
+        }
+        assert_eq!(value["s"].as_bool(), None);
+        assert_eq!(value["s"].as_number(), None);
+        assert_eq!(value["s"].as_array(), None);
+        assert_eq!(value["s"].as_object(), None);
+        assert!(!value["s"].is_number() && value["s"].is_string() && value["b"].is_bool());
+    }
+
+    #[test]
+    fn get() {
+        let value = json_parse(DOC).unwrap();
+        assert_eq!(
+            value.get("a").and_then(|a| a.get(1)),
+            Some(&JsonValue::String("x".to_string()))
+        );
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value.get("a").and_then(|a| a.get(2)), None);
+        assert_eq!(value.get(0), None);
+        assert_eq!(value["a"].get("k"), None);
+        assert_eq!(value.get("n"), Some(&Null));
+        let key = "o".to_string();
+        assert_eq!(value.get(&key).and_then(|o| o.get("k")), Some(&Num(Int(2))));
+    }
+
+    #[test]
+    fn index_never_panics() {
+        let value = json_parse(DOC).unwrap();
+        assert_eq!(value["missing"], Null);
+        assert_eq!(value["a"][5], Null);
+        assert_eq!(value["s"][0], Null);
+        assert_eq!(value["missing"]["deeper"][3], Null);
+    }
+
+    #[test]
+    fn mutable_access() {
+        let mut value = json_parse(DOC).unwrap();
+        assert_eq!(value.as_array_mut(), None);
+        if let Some(a) = value.get_mut("a").and_then(|a| a.as_array_mut()) {
+            a.push(Bool(false));
+        }
+        if let Some(k) = value.get_mut("o").and_then(|o| o.get_mut("k")) {
+            *k = Num(Int(3));
+        }
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("new".to_string(), Null);
+        assert_eq!(value["a"][2], Bool(false));
+        assert_eq!(value["o"]["k"], Num(Int(3)));
+        assert!(value.get("new").is_some());
+    }
+
+    #[test]
+    fn borrowed_has_the_same_accessors() {
+        let value = crate::json_parser::parse_borrowed(DOC).unwrap();
+        assert_eq!(value["i"].as_i64(), Some(-3));
+        assert_eq!(value["s"].as_str(), Some("text"));
+        assert_eq!(
+            value
+                .get("a")
+                .and_then(|a| a.get(1))
+                .and_then(|x| x.as_str()),
+            Some("x")
+        );
+        assert!(value["a"][9].is_null());
+        assert_eq!(value["o"].as_object().map(|o| o.len()), Some(1));
+    }
+}
+
+mod mutation {
+    pub use super::*;
+
+    #[test]
+    fn index_mut_creates_keys() {
+        let mut value = Null;
+        value["a"]["b"] = Num(Int(1));
+        value["a"]["c"] = Bool(true);
+        value["list"] = Array(vec![Null]);
+        value["list"][0] = Num(Int(2));
+        assert_eq!(
+            value,
+            json_parse("{\"a\": {\"b\": 1, \"c\": true}, \"list\": [2]}").unwrap()
+        );
+        // reading a missing key through IndexMut inserts it as null
+        let _ = &mut value["missing"];
+        assert_eq!(value.get("missing"), Some(&Null));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_mut_out_of_range() {
+        let mut value = Array(vec![]);
+        value[0] = Null;
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot get an entry on a number")]
+    fn index_mut_wrong_type() {
+        let mut value = Num(Int(1));
+        value["a"] = Null;
+    }
+
+    #[test]
+    fn insert_push_remove() {
+        let mut value = Null;
+        assert_eq!(value.insert("a", Num(Int(1))), None);
+        assert_eq!(value.insert("a", Num(Int(2))), Some(Num(Int(1))));
+        value["list"].push(Num(Int(3)));
+        value["list"].push(Num(Int(4)));
+        value["list"].push(Num(Int(5)));
+        assert_eq!(value["list"].remove(1), Some(Num(Int(4))));
+        assert_eq!(value["list"].remove(5), None);
+        assert_eq!(value["list"], Array(vec![Num(Int(3)), Num(Int(5))]));
+        assert_eq!(value.remove("a"), Some(Num(Int(2))));
+        assert_eq!(value.remove("a"), None);
+        assert_eq!(value.remove(0), None);
+    }
+
+    #[test]
+    fn entry_and_take() {
+        let mut value = json_parse("{\"count\": 1}").unwrap();
+        *value.entry("count").or_insert(Num(Int(0))) = Num(Int(2));
+        value.entry("other").or_insert(Num(Int(7)));
+        assert_eq!(value["count"], Num(Int(2)));
+        assert_eq!(value["other"], Num(Int(7)));
+
+        let taken = value["other"].take();
+        assert_eq!(taken, Num(Int(7)));
+        assert_eq!(value["other"], Null);
+    }
+
+    #[test]
+    fn pointers() {
+        let mut value =
+            json_parse("{\"a/b\": {\"m~n\": [10, 20]}, \"\": 1, \"list\": [{\"k\": null}]}")
+                .unwrap();
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(value.pointer("/a~1b/m~0n/1"), Some(&Num(Int(20))));
+        assert_eq!(value.pointer("/"), Some(&Num(Int(1))));
+        assert_eq!(value.pointer("/list/0/k"), Some(&Null));
+        assert_eq!(value.pointer("/list/01"), None);
+        assert_eq!(value.pointer("/list/1"), None);
+        assert_eq!(value.pointer("/list/-"), None);
+        assert_eq!(value.pointer("list"), None);
+
+        *value.pointer_mut("/a~1b/m~0n/0").unwrap() = Num(Int(11));
+        value
+            .pointer_mut("/list/0")
+            .unwrap()
+            .insert("k", Bool(true));
+        assert_eq!(value["a/b"]["m~n"][0], Num(Int(11)));
+        assert_eq!(value["list"][0]["k"], Bool(true));
+        assert!(value.pointer_mut("/missing/0").is_none());
+    }
+}
+
+mod json_macro {
+    pub use super::*;
+    use crate::json;
+
+    #[test]
+    fn literals() {
+        assert_eq!(json!(null), Null);
+        assert_eq!(json!(true), Bool(true));
+        assert_eq!(json!(false), Bool(false));
+        assert_eq!(json!(-12), Num(Int(-12)));
+        assert_eq!(json!(2.5), Num(Float(2.5)));
+        assert_eq!(json!("text"), JsonValue::String("text".to_string()));
+        assert_eq!(json!([]), Array(vec![]));
+        assert_eq!(json!({}), Obj(Map::new()));
+    }
+
+    #[test]
+    fn nested() {
+        let value = json!({
+            "list": [1, null, true, false, [2, [3]], {"a": {}}, "s",],
+            "obj": {"x": -1.5, "y": [], "z": null,},
+            "empty": {}
+        });
+        assert_eq!(
+            value,
+            json_parse(
+                "{\"list\": [1, null, true, false, [2, [3]], {\"a\": {}}, \"s\"],
+                \"obj\": {\"x\": -1.5, \"y\": [], \"z\": null}, \"empty\": {}}"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn interpolation() {
+        let count = 4u8;
+        let name = String::from("fuz");
+        let key = "dynamic";
+        let inner = json!([1, 2]);
+        let value = json!({
+            "count": count * 2,
+            "name": &name,
+            key: name.len() > 2,
+            (format!("{key}_2")): 'c',
+            "inner": inner,
+            "big": u128::MAX,
+            "items": [count, count as i64 - 10, f64::from(count) / 8.0, {"n": name.as_str()}]
+        });
+        assert_eq!(value["count"], Num(Int(8)));
+        assert_eq!(value["name"].as_str(), Some("fuz"));
+        assert_eq!(value["dynamic"], Bool(true));
+        assert_eq!(value["dynamic_2"].as_str(), Some("c"));
+        assert_eq!(value["inner"], json!([1, 2]));
+        assert_eq!(value["big"], Num(UInt(u128::MAX)));
+        assert_eq!(value["items"].encode(), "[4,-6,0.5,{\"n\":\"fuz\"}]");
+    }
+}
+
+mod conversions {
+    pub use super::*;
+    use crate::error::ConversionError;
+    use crate::json;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn into_json_value() {
+        assert_eq!(JsonValue::from(-3i64), Num(Int(-3)));
+        assert_eq!(JsonValue::from(7usize), Num(Int(7)));
+        assert_eq!(JsonValue::from(0.5f32), Num(Float(0.5)));
+        assert_eq!(JsonValue::from(true), Bool(true));
+        assert_eq!(JsonValue::from("s"), JsonValue::String("s".to_string()));
+        assert_eq!(JsonValue::from(None::<i32>), Null);
+        assert_eq!(JsonValue::from(Some("x")), json!("x"));
+        assert_eq!(JsonValue::from(vec![Some(1), None]), json!([1, null]));
+        assert_eq!(JsonValue::from(&[1u8, 2][..]), json!([1, 2]));
+
+        let map: HashMap<String, Vec<bool>> =
+            HashMap::from([("a".to_string(), vec![true]), ("b".to_string(), vec![])]);
+        assert_eq!(JsonValue::from(map), json!({"a": [true], "b": []}));
+        let map = BTreeMap::from([("k", 1.5)]);
+        assert_eq!(JsonValue::from(map), json!({"k": 1.5}));
+    }
+
+    #[test]
+    fn collect() {
+        let array: JsonValue = (1..=3).collect();
+        assert_eq!(array, json!([1, 2, 3]));
+        let object: JsonValue = ["a", "b"].iter().map(|k| (*k, k.len())).collect();
+        assert_eq!(object, json!({"a": 1, "b": 1}));
+    }
+
+    #[test]
+    fn out_of_json_value() {
+        let value = json!({"n": 300, "neg": -1, "f": 2.5, "s": "t", "list": [1, 2], "null": null});
+        assert_eq!(i64::try_from(&value["n"]), Ok(300));
+        assert_eq!(u16::try_from(&value["n"]), Ok(300));
+        assert_eq!(f64::try_from(&value["n"]), Ok(300.0));
+        assert_eq!(f32::try_from(&value["f"]), Ok(2.5));
+        assert_eq!(String::try_from(&value["s"]), Ok("t".to_string()));
+        assert_eq!(<&str>::try_from(&value["s"]), Ok("t"));
+        assert_eq!(Vec::<u8>::try_from(&value["list"]), Ok(vec![1, 2]));
+        assert_eq!(Option::<u8>::try_from(&value["null"]), Ok(None));
+        assert_eq!(Option::<u8>::try_from(&value["list"][0]), Ok(Some(1)));
+        assert_eq!(bool::try_from(&json!(false)), Ok(false));
+        let map = HashMap::<String, u8>::try_from(&json!({"a": 1, "b": 2})).unwrap();
+        assert_eq!(map.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn conversion_errors() {
+        let value = json!({"n": 300, "neg": -1, "f": 2.5, "s": "t"});
+        let e = u8::try_from(&value["n"]).unwrap_err();
+        assert_eq!(e.to_string(), "expected u8 found the number 300");
+        assert_eq!(
+            u64::try_from(&value["neg"]).unwrap_err().found(),
+            "the number -1"
+        );
+        assert_eq!(i64::try_from(&value["f"]).unwrap_err().expected(), "i64");
+        assert_eq!(
+            bool::try_from(&value["s"]).unwrap_err(),
+            ConversionError::new("a bool", "a string")
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(&value).unwrap_err().to_string(),
+            "expected an array found an object"
+        );
+    }
+
+    #[test]
+    fn error_path() {
+        let value = json!({"a/b": [{"c": [1, "x"]}]});
+        let e = BTreeMap::<String, Vec<HashMap<String, Vec<i32>>>>::try_from(&value).unwrap_err();
+        assert_eq!(e.path(), "/a~1b/0/c/1");
+        assert_eq!(e.to_string(), "expected i32 found a string at /a~1b/0/c/1");
+        assert_eq!(value.pointer(e.path()), Some(&json!("x")));
+    }
+}
+
+mod json_traits {
+    pub use super::*;
+    use crate::json;
+    use crate::{FromJson, ToJson};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn std_types() {
+        let value = (vec![Some(1u8), None]).to_json();
+        assert_eq!(value, json!([1, null]));
+        assert_eq!(
+            Vec::<Option<u8>>::from_json(&value),
+            Ok(vec![Some(1), None])
+        );
+
+        let map = BTreeMap::from([("a".to_string(), vec!['x', 'y'])]);
+        let value = map.to_json();
+        assert_eq!(value, json!({"a": ["x", "y"]}));
+        assert_eq!(BTreeMap::from_json(&value), Ok(map));
+
+        assert_eq!(["s"; 2].to_json(), json!(["s", "s"]));
+        assert_eq!(Box::new(2.5).to_json(), json!(2.5));
+        assert_eq!(
+            JsonValue::from_json(&json!({"k": []})),
+            Ok(json!({"k": []}))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            char::from_json(&json!("ab")).unwrap_err().to_string(),
+            "expected a char found a string of length 2"
+        );
+        let e = BTreeMap::<String, Vec<u8>>::from_json(&json!({"a": [1, -1]})).unwrap_err();
+        assert_eq!(e.to_string(), "expected u8 found the number -1 at /a/1");
+    }
+}
+
+#[cfg(feature = "derive")]
+mod derive {
+    use crate::error::ConversionError;
+    use crate::json;
+    use crate::{FromJson, ToJson};
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Server {
+        host: String,
+        #[json(rename = "portNumber")]
+        port: u16,
+        #[json(default)]
+        tags: Vec<String>,
+        #[json(default = "default_retries")]
+        retries: u8,
+        #[json(skip)]
+        connected: bool,
+        timeout: Option<f64>,
+        #[json(flatten)]
+        limits: Limits,
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Limits {
+        max_body: u64,
+    }
+
+    fn default_retries() -> u8 {
+        3
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Meters(f64);
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Pair<T>(T, T);
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    enum Shape {
+        Empty,
+        #[json(rename = "circle")]
+        Circle(Meters),
+        Line(Pair<i32>),
+        Rect {
+            width: u32,
+            height: u32,
+        },
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    #[json(tag = "type")]
+    enum Event {
+        Start,
+        Move { x: i32 },
+        Resize(Size),
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct Size {
+        w: u32,
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    #[json(tag = "t", content = "c")]
+    enum Message {
+        Ping,
+        Text(String),
+        Point(i32, i32),
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    #[json(untagged)]
+    enum Id {
+        Missing,
+        Number(u64),
+        Name(String),
+    }
+
+    #[test]
+    fn struct_attributes() {
+        let server = Server {
+            host: "localhost".to_string(),
+            port: 8080,
+            tags: vec![],
+            retries: 3,
+            connected: true,
+            timeout: None,
+            limits: Limits { max_body: 1024 },
+        };
+        let value = server.to_json();
+        assert_eq!(
+            value,
+            json!({
+                "host": "localhost", "portNumber": 8080, "tags": [], "retries": 3,
+                "timeout": null, "max_body": 1024
+            })
+        );
+
+        let read =
+            Server::from_json(&json!({"host": "localhost", "portNumber": 8080, "max_body": 1024}));
+        assert_eq!(
+            read,
+            Ok(Server {
+                connected: false,
+                ..server
+            })
+        );
+    }
+
+    #[test]
+    fn tuple_and_unit_shapes() {
+        assert_eq!(Meters(1.5).to_json(), json!(1.5));
+        assert_eq!(Pair(1, 2).to_json(), json!([1, 2]));
+        assert_eq!(Pair::<u8>::from_json(&json!([3, 4])), Ok(Pair(3, 4)));
+        assert_eq!(
+            Pair::<u8>::from_json(&json!([3])).unwrap_err().to_string(),
+            "expected Pair found an array of length 1"
+        );
+    }
+
+    #[test]
+    fn external_tagging() {
+        let shapes = vec![
+            Shape::Empty,
+            Shape::Circle(Meters(2.0)),
+            Shape::Line(Pair(-1, 1)),
+            Shape::Rect {
+                width: 3,
+                height: 4,
+            },
+        ];
+        let value = shapes.to_json();
+        assert_eq!(
+            value,
+            json!([
+                "Empty",
+                {"circle": 2.0},
+                {"Line": [-1, 1]},
+                {"Rect": {"width": 3, "height": 4}}
+            ])
+        );
+        assert_eq!(Vec::<Shape>::from_json(&value), Ok(shapes));
+        assert_eq!(
+            Shape::from_json(&json!("Square")),
+            Err(ConversionError::new(
+                "Shape",
+                "the unknown variant `Square`"
+            ))
+        );
+    }
+
+    #[test]
+    fn internal_tagging() {
+        let events = vec![
+            Event::Start,
+            Event::Move { x: 4 },
+            Event::Resize(Size { w: 9 }),
+        ];
+        let value = events.to_json();
+        assert_eq!(
+            value,
+            json!([{"type": "Start"}, {"type": "Move", "x": 4}, {"type": "Resize", "w": 9}])
+        );
+        assert_eq!(Vec::<Event>::from_json(&value), Ok(events));
+        assert_eq!(
+            Event::from_json(&json!({"x": 4})).unwrap_err().to_string(),
+            "expected Event found nothing at /type"
+        );
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    #[json(tag = "type")]
+    enum Setting {
+        Level(i32),
+        Name(Option<String>),
+        Limits(Size),
+        Wrapped(ValueHolder),
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    struct ValueHolder {
+        value: u8,
+    }
+
+    #[test]
+    fn internal_tagging_non_object_content() {
+        let settings = vec![
+            Setting::Level(1),
+            Setting::Name(None),
+            Setting::Name(Some("fuz".to_string())),
+            Setting::Limits(Size { w: 2 }),
+            Setting::Wrapped(ValueHolder { value: 3 }),
+        ];
+        let value = settings.to_json();
+        assert_eq!(
+            value,
+            json!([
+                {"type": "Level", "value": 1},
+                {"type": "Name", "value": null},
+                {"type": "Name", "value": "fuz"},
+                {"type": "Limits", "w": 2},
+                {"type": "Wrapped", "value": {"value": 3}}
+            ])
+        );
+        assert_eq!(Vec::<Setting>::from_json(&value), Ok(settings));
+        // the same variant without the extra object, as serde writes it
+        assert_eq!(
+            Setting::from_json(&json!({"type": "Wrapped", "value": 3})),
+            Ok(Setting::Wrapped(ValueHolder { value: 3 }))
+        );
+        assert_eq!(
+            Setting::from_json(&json!({"type": "Level", "value": "high"}))
+                .unwrap_err()
+                .to_string(),
+            "expected i32 found a string at /value"
+        );
+    }
+
+    #[derive(Debug, PartialEq, ToJson, FromJson)]
+    #[json(tag = "type")]
+    enum Payload {
+        Any(crate::values::JsonValue),
+        Members(std::collections::HashMap<String, i32>),
+    }
+
+    #[test]
+    fn internal_tagging_round_trip() {
+        let payloads = vec![
+            Payload::Any(json!({"x": 1})),
+            Payload::Any(json!({"value": 5})),
+            Payload::Any(json!(5)),
+            Payload::Any(json!({"type": "Members", "x": 1})),
+            Payload::Any(json!({})),
+            Payload::Members([("x".to_string(), 1)].into()),
+            Payload::Members([("value".to_string(), 2)].into()),
+            Payload::Members([("type".to_string(), 3)].into()),
+        ];
+        let value = payloads.to_json();
+        assert_eq!(
+            value,
+            json!([
+                {"type": "Any", "x": 1},
+                {"type": "Any", "value": {"value": 5}},
+                {"type": "Any", "value": 5},
+                {"type": "Any", "value": {"type": "Members", "x": 1}},
+                {"type": "Any"},
+                {"type": "Members", "x": 1},
+                {"type": "Members", "value": {"value": 2}},
+                {"type": "Members", "value": {"type": 3}}
+            ])
+        );
+        assert_eq!(Vec::<Payload>::from_json(&value), Ok(payloads));
+    }
+
+    #[test]
+    fn adjacent_tagging() {
+        let messages = vec![
+            Message::Ping,
+            Message::Text("hi".to_string()),
+            Message::Point(1, 2),
+        ];
+        let value = messages.to_json();
+        assert_eq!(
+            value,
+            json!([{"t": "Ping"}, {"t": "Text", "c": "hi"}, {"t": "Point", "c": [1, 2]}])
+        );
+        assert_eq!(Vec::<Message>::from_json(&value), Ok(messages));
+    }
+
+    #[test]
+    fn untagged() {
+        let ids = vec![Id::Missing, Id::Number(7), Id::Name("seven".to_string())];
+        let value = ids.to_json();
+        assert_eq!(value, json!([null, 7, "seven"]));
+        assert_eq!(Vec::<Id>::from_json(&value), Ok(ids));
+        assert_eq!(
+            Id::from_json(&json!(true)).unwrap_err().to_string(),
+            "expected Id found a bool"
+        );
+    }
+
+    #[test]
+    fn error_paths() {
+        let value = json!({"host": "h", "portNumber": 70000, "max_body": 1});
+        assert_eq!(
+            Server::from_json(&value).unwrap_err().to_string(),
+            "expected u16 found the number 70000 at /portNumber"
+        );
+        let value = json!({"portNumber": 80, "max_body": 1});
+        assert_eq!(
+            Server::from_json(&value).unwrap_err().to_string(),
+            "expected a string found nothing at /host"
+        );
+        let value = json!([{"Rect": {"width": 1, "height": "2"}}]);
+        let e = Vec::<Shape>::from_json(&value).unwrap_err();
+        assert_eq!(e.path(), "/0/Rect/height");
+        assert_eq!(value.pointer(e.path()), Some(&json!("2")));
+        let value = json!({"t": "Point", "c": [1, null]});
+        assert_eq!(Message::from_json(&value).unwrap_err().path(), "/c/1");
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_support {
+    pub use super::*;
+    use crate::json;
+    use crate::json_parser::DuplicateKeys;
+    use crate::values::JsonNum;
+    use crate::{from_str, from_str_with_options};
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config<'a> {
+        name: &'a str,
+        owned: String,
+        port: u16,
+        ratio: f64,
+        tags: Vec<Option<u8>>,
+        mode: Mode,
+        shapes: Vec<Shape>,
+        extra: HashMap<String, bool>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Line(i32, i32),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[test]
+    fn deserialize_struct() {
+        let config: Config = from_str(
+            r#"{
+                "name": "fuz", "owned": "a\nb", "port": 8080, "ratio": 1e-1,
+                "tags": [1, null], "mode": "Slow",
+                "shapes": ["Empty", {"Circle": 2.5}, {"Line": [1, -1]}, {"Rect": {"w": 1, "h": 2}}],
+                "extra": {"x": true}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "fuz",
+                owned: "a\nb".to_string(),
+                port: 8080,
+                ratio: 0.1,
+                tags: vec![Some(1), None],
+                mode: Mode::Slow,
+                shapes: vec![
+                    Shape::Empty,
+                    Shape::Circle(2.5),
+                    Shape::Line(1, -1),
+                    Shape::Rect { w: 1, h: 2 }
+                ],
+                extra: HashMap::from([("x".to_string(), true)]),
+            }
+        );
+    }
+
+    #[test]
+    fn value_types() {
+        let source = r#"{"a": [1, -2, 1.5, 340282366920938463463374607431768211455], "b": null, "c": "\u00e9"}"#;
+        assert_eq!(from_str::<JsonValue>(source), json_parse(source));
+        assert_eq!(from_str::<JsonNum>("-7"), Ok(Int(-7)));
+        assert!(from_str::<JsonNum>("\"7\"").is_err());
+
+        let value = json!([null, true, -1, 2.5, "q\"", {"k": [u64::MAX]}]);
+        let serialized = serde_json::to_string(&value).unwrap();
+        assert_eq!(serialized, value.encode());
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&serialized).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn borrowed_serializes_like_owned() {
+        let source = r#"{"a": [1, "x\ty", {"b": null}], "c": 2.5}"#;
+        let borrowed = crate::json_parser::parse_borrowed(source).unwrap();
+        let serialized = serde_json::to_string(&borrowed).unwrap();
+        assert_eq!(
+            serde_json::from_str::<JsonValue>(&serialized).unwrap(),
+            json_parse(source).unwrap()
+        );
+    }
+
+    #[test]
+    fn raw_numbers_lose_digits() {
+        let huge = "123456789012345678901234567890123456789012345678901234567890";
+        let raw = |text: &str| JsonNum::Raw(text.to_string());
+        let serialize = |num: JsonNum| serde_json::to_string(&num).unwrap();
+        assert_eq!(serialize(raw("-12")), "-12");
+        assert_eq!(
+            serialize(raw("340282366920938463463374607431768211455")),
+            "340282366920938463463374607431768211455"
+        );
+        // only the nearest f64 is kept
+        assert_eq!(serialize(raw("0.1000000000000000055511")), "0.1");
+        assert_eq!(serialize(raw(huge)), "1.2345678901234567e+59");
+        assert_eq!(serialize(raw("1e400")), "null");
+        // encoding keeps every digit
+        assert_eq!(JsonValue::Num(raw(huge)).encode(), huge);
+    }
+
+    #[test]
+    fn size_hint_is_not_trusted() {
+        use serde::de::value::{Error, SeqDeserializer};
+
+        /// claims to have more elements than fit in memory but has none
+        struct Lying;
+
+        impl Iterator for Lying {
+            type Item = u8;
+
+            fn next(&mut self) -> Option<u8> {
+                None
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (usize::MAX, Some(usize::MAX))
+            }
+        }
+
+        let deserializer = SeqDeserializer::<_, Error>::new(Lying);
+        assert_eq!(JsonValue::deserialize(deserializer), Ok(Array(vec![])));
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let e = from_str::<Shape>("{\"Rect\": {\"w\": 1}}").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Custom);
+        assert_eq!(e.message(), "missing field `h`");
+        assert_eq!(e.column(), 10);
+
+        let e = from_str::<Vec<u8>>("[1,\n 300]").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Custom);
+        assert_eq!((e.line(), e.column()), (2, 2));
+
+        let e = from_str::<Vec<u8>>("[1, 2,]").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedChar);
+        assert_eq!(
+            from_str::<u8>("1 2").unwrap_err().kind(),
+            ErrorKind::TrailingCharacters
+        );
+        assert_eq!(
+            from_str::<u8>(" ").unwrap_err().kind(),
+            ErrorKind::EmptyInput
+        );
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(try_from = "u32")]
+    struct Even(#[allow(dead_code)] u32);
+
+    impl TryFrom<u32> for Even {
+        type Error = String;
+
+        fn try_from(value: u32) -> std::result::Result<Self, String> {
+            match value % 2 {
+                0 => Ok(Even(value)),
+                _ => Err(format!("{value} is odd")),
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Pair {
+        #[allow(dead_code)]
+        first: Even,
+        #[allow(dead_code)]
+        second: Even,
+    }
+
+    #[test]
+    fn conversion_errors_have_positions() {
+        let e = from_str::<Even>(" 3").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Custom);
+        assert_eq!((e.line(), e.column(), e.offset()), (1, 2, 1));
+
+        let e = from_str::<Pair>("{\"first\": 2,\n \"second\": 5}").unwrap_err();
+        assert_eq!(e.message(), "5 is odd");
+        assert_eq!((e.line(), e.column()), (2, 12));
+
+        let e = from_str::<Vec<Even>>("[2, 4, 7]").unwrap_err();
+        assert_eq!((e.line(), e.column()), (1, 8));
+
+        let options = ParseOptions {
+            allow_empty_input: true,
+            ..ParseOptions::default()
+        };
+        let e = from_str_with_options::<u8>("  ", &options).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Custom);
+        assert_eq!((e.line(), e.column()), (1, 3));
+    }
+
+    #[test]
+    fn options_apply() {
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            max_depth: 2,
+            ..ParseOptions::default()
+        };
+        let e = from_str_with_options::<HashMap<String, u8>>("{\"a\": 1, \"a\": 2}", &options)
+            .unwrap_err();
+        assert_eq!((e.kind(), e.offset()), (ErrorKind::DuplicateKey, 9));
+        assert_eq!(
+            from_str_with_options::<JsonValue>("[[[]]]", &options)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::DepthExceeded
+        );
+        assert_eq!(
+            from_str::<HashMap<String, u8>>("{\"a\": 1, \"a\": 2}").unwrap()["a"],
+            2
+        );
+    }
+}
+
+mod integer_overflow {
+    pub use super::*;
+    use crate::json_parser::IntegerOverflow;
+
+    const U128_MAX: &str = "340282366920938463463374607431768211455";
+    const TOO_BIG: &str = "340282366920938463463374607431768211456";
+
+    fn with_policy(
+        json_str: &str,
+        integer_overflow: IntegerOverflow,
+    ) -> crate::error::Result<JsonValue> {
+        let options = ParseOptions {
+            integer_overflow,
+            ..ParseOptions::strict()
+        };
+        parse_with_options(json_str, &options)
+    }
+
+    #[test]
+    fn full_unsigned_range() {
+        assert_eq!(json_parse(i128::MAX.to_string()), Ok(Num(Int(i128::MAX))));
+        assert_eq!(json_parse(i128::MIN.to_string()), Ok(Num(Int(i128::MIN))));
+        assert_eq!(
+            json_parse("170141183460469231731687303715884105728"),
+            Ok(Num(UInt(1 << 127)))
+        );
+        assert_eq!(json_parse(U128_MAX), Ok(Num(UInt(u128::MAX))));
+        assert_eq!(json_parse(U128_MAX).unwrap().encode(), U128_MAX);
+    }
+
+    #[test]
+    fn overflow_is_an_error_by_default() {
+        for json_str in [TOO_BIG, "-170141183460469231731687303715884105729"] {
+            let e = json_parse(json_str).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+            assert!(e.message().contains("out of range"), "{e}");
+        }
+    }
+
+    #[test]
+    fn fallback() {
+        assert_eq!(
+            with_policy(TOO_BIG, IntegerOverflow::Float),
+            Ok(Num(Float(3.402823669209385e38)))
+        );
+        assert_eq!(
+            with_policy(
+                "-170141183460469231731687303715884105729",
+                IntegerOverflow::Float
+            ),
+            Ok(Num(Float(-1.7014118346046923e38)))
+        );
+        assert_eq!(
+            with_policy(TOO_BIG, IntegerOverflow::Raw),
+            Ok(Num(Raw(TOO_BIG.to_string())))
+        );
+        // numbers that fit are not affected
+        assert_eq!(
+            with_policy("[1, 2.5]", IntegerOverflow::Raw),
+            json_parse("[1, 2.5]")
+        );
+        let too_big_for_float = "1".repeat(400);
+        assert!(with_policy(&too_big_for_float, IntegerOverflow::Float).is_err());
+    }
+
+    #[test]
+    fn checked_accessors() {
+        assert_eq!(Int(7).as_u32(), Some(7));
+        assert_eq!(Int(-7).as_u32(), None);
+        assert_eq!(Int(u32::MAX as i128 + 1).as_u32(), None);
+        assert_eq!(Int(u64::MAX as i128).as_u64(), Some(u64::MAX));
+        assert_eq!(Int(i64::MIN as i128).as_i64(), Some(i64::MIN));
+        assert_eq!(Int(i64::MIN as i128 - 1).as_i64(), None);
+        assert_eq!(UInt(u128::MAX).as_u128(), Some(u128::MAX));
+        assert_eq!(UInt(u128::MAX).as_i128(), None);
+        assert_eq!(UInt(u128::MAX).as_u64(), None);
+        assert_eq!(Float(1.0).as_u32(), None);
+        assert_eq!(Raw(U128_MAX.to_string()).as_u128(), Some(u128::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected Float found UInt")]
+    fn unwrap_float_on_uint() {
+        UInt(u128::MAX).unwrap_float();
+    }
+}
+
+mod arbitrary_precision {
+    pub use super::*;
+
+    const OPTIONS: ParseOptions = ParseOptions {
+        arbitrary_precision: true,
+        ..ParseOptions::strict()
+    };
+
+    fn raw(json_str: &str) -> JsonValue {
+        parse_with_options(json_str, &OPTIONS).unwrap()
+    }
+
+    #[test]
+    fn keeps_source_text() {
+        assert_eq!(
+            raw("0.1000000000000000055511"),
+            Num(Raw("0.1000000000000000055511".to_string()))
+        );
+        assert_eq!(raw("-12E+3"), Num(Raw("-12E+3".to_string())));
+        let huge = "123456789012345678901234567890123456789012345678901234567890";
+        assert_eq!(raw(huge), Num(Raw(huge.to_string())));
+        assert!(json_parse(huge).is_err());
+    }
+
+    #[test]
+    fn lossless_encode() {
+        let json_str =
+            "[0.1000000000000000055511,1e400,-0,1.50,340282366920938463463374607431768211456]";
+        assert_eq!(raw(json_str).encode(), json_str);
+    }
+
+    #[test]
+    fn leading_decimal_point_is_normalized() {
+        let options = ParseOptions {
+            allow_leading_decimal_point: true,
+            ..OPTIONS
+        };
+        assert_eq!(
+            parse_with_options("[.5, -.25]", &options).unwrap().encode(),
+            "[0.5,-0.25]"
+        );
+    }
+
+    #[test]
+    fn checked_conversions() {
+        let big = raw("9223372036854775808");
+        let Num(num) = &big else {
+            panic!("expected a number")
+        };
+        assert_eq!(num.as_i64(), None);
+        assert_eq!(num.as_u64(), Some(9223372036854775808));
+        assert_eq!(num.as_i128(), Some(9223372036854775808));
+        assert_eq!(num.as_f64(), Some(9223372036854775808.0));
+
+        assert_eq!(Raw("-1".to_string()).as_u64(), None);
+        assert_eq!(Raw("-1".to_string()).as_i64(), Some(-1));
+        assert_eq!(Raw("1.5".to_string()).as_i64(), None);
+        assert_eq!(Raw("1.5".to_string()).as_f64(), Some(1.5));
+        assert_eq!(Raw("1e400".to_string()).as_f64(), None);
+        assert_eq!(Raw("1e40".to_string()).as_i128(), None);
+
+        assert_eq!(Int(-5).as_i64(), Some(-5));
+        assert_eq!(Int(i128::MAX).as_u64(), None);
+        assert_eq!(Float(2.0).as_i64(), None);
+        assert_eq!(Float(2.5).as_f64(), Some(2.5));
+    }
+}
+
+#[cfg(feature = "preserve_order")]
+mod preserve_order {
+    pub use super::*;
+
+    #[test]
+    fn parse_and_encode_keep_order() {
+        let json_str = "{\"z\":1,\"a\":{\"y\":2,\"b\":3},\"m\":[{\"q\":4,\"c\":5}]}";
+        assert_eq!(json_parse(json_str).unwrap().encode(), json_str);
+        assert_eq!(
+            crate::json_parser::parse_borrowed(json_str)
+                .unwrap()
+                .encode(),
+            json_str
+        );
+    }
+
+    #[test]
+    fn mutation_keeps_order() {
+        let mut value = json_parse("{\"z\": 1, \"a\": 2, \"m\": 3}").unwrap();
+        let Obj(map) = &mut value else {
+            panic!("expected an object")
+        };
+        map.insert("b".to_string(), Num(Int(4)));
+        map.insert("z".to_string(), Num(Int(5)));
+        map.remove("a");
+        assert_eq!(value.encode(), "{\"z\":5,\"m\":3,\"b\":4}");
+        assert_eq!(value.to_string(), "{\"z\":5, \"m\":3, \"b\":4}");
+    }
+}
+
 mod invalid_json {
     pub use super::*;
 
@@ -0,0 +1,407 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::convert::{collect_array, collect_object};
+use crate::error::ConversionError;
+use crate::value_ref::JsonValueRef;
+use crate::values::{JsonNum, JsonValue};
+
+/// turn a Rust value into a [JsonValue]
+///
+/// implemented for the std types that have a JSON form and derivable with
+/// `#[derive(ToJson)]` when the `derive` feature is on
+pub trait ToJson {
+    /// build the JSON form of the value
+    fn to_json(&self) -> JsonValue;
+}
+
+/// read a Rust value back out of a [JsonValue]
+///
+/// implemented for the std types that have a JSON form and derivable with
+/// `#[derive(FromJson)]` when the `derive` feature is on
+pub trait FromJson: Sized {
+    /// read the value, the error says what was wrong and where in `value` it was
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError>;
+}
+
+macro_rules! json_scalar {
+    ($($scalar:ty),*) => {
+        $(
+            impl ToJson for $scalar {
+                fn to_json(&self) -> JsonValue {
+                    JsonValue::from(*self)
+                }
+            }
+
+            impl FromJson for $scalar {
+                fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+                    Self::try_from(value)
+                }
+            }
+        )*
+    };
+}
+
+json_scalar!(bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl ToJson for char {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(*self)
+    }
+}
+
+/// a `JsonValue::String` of exactly one character
+impl FromJson for char {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        let text =
+            <&str>::try_from(value).map_err(|e| ConversionError::new("a char", e.found()))?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(ConversionError::new(
+                "a char",
+                format!("a string of length {}", text.chars().count()),
+            )),
+        }
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(self)
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::from(self)
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        Self::try_from(value)
+    }
+}
+
+/// `()` is `JsonValue::Null`
+impl ToJson for () {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Null
+    }
+}
+
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+
+/// any value is read as it is
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for JsonValueRef<'_> {
+    fn to_json(&self) -> JsonValue {
+        self.to_owned()
+    }
+}
+
+impl ToJson for JsonNum {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Num(self.clone())
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+/// `None` is `JsonValue::Null`
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::Null, T::to_json)
+    }
+}
+
+/// `JsonValue::Null` is `None`, so a missing member can be read as `None` too
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(T::to_json).collect())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        collect_array(value, T::from_json)
+    }
+}
+
+/// build a `JsonValue::Obj` out of key value pairs
+fn object_to_json<'a, K, T>(members: impl Iterator<Item = (&'a K, &'a T)>) -> JsonValue
+where
+    K: AsRef<str> + 'a,
+    T: ToJson + 'a,
+{
+    JsonValue::Obj(
+        members
+            .map(|(key, member)| (key.as_ref().to_string(), member.to_json()))
+            .collect(),
+    )
+}
+
+impl<K: AsRef<str>, T: ToJson, S> ToJson for HashMap<K, T, S> {
+    fn to_json(&self) -> JsonValue {
+        object_to_json(self.iter())
+    }
+}
+
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        collect_object(value, T::from_json)
+    }
+}
+
+impl<K: AsRef<str>, T: ToJson> ToJson for BTreeMap<K, T> {
+    fn to_json(&self) -> JsonValue {
+        object_to_json(self.iter())
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        collect_object(value, T::from_json)
+    }
+}
+
+#[cfg(feature = "preserve_order")]
+impl<K: AsRef<str>, T: ToJson, S> ToJson for indexmap::IndexMap<K, T, S> {
+    fn to_json(&self) -> JsonValue {
+        object_to_json(self.iter())
+    }
+}
+
+#[cfg(feature = "preserve_order")]
+impl<T: FromJson, S: BuildHasher + Default> FromJson for indexmap::IndexMap<String, T, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, ConversionError> {
+        collect_object(value, T::from_json)
+    }
+}
+
+/// the functions the code from `fuz_json_derive` calls, not meant to be used on its own
+#[doc(hidden)]
+pub mod derive_support {
+    pub use crate::error::ConversionError;
+    pub use crate::traits::{FromJson, ToJson};
+    pub use crate::values::{JsonValue, Map};
+
+    pub type Object = Map<String, JsonValue>;
+
+    pub fn mismatch(expected: &str, value: &JsonValue) -> ConversionError {
+        crate::convert::mismatch(expected, value)
+    }
+
+    /// an object with the single member `key`
+    pub fn single(key: &str, value: JsonValue) -> JsonValue {
+        let mut object = Object::new();
+        object.insert(key.to_string(), value);
+        JsonValue::Obj(object)
+    }
+
+    /// add the members of `value` to `object`, anything other than an object adds nothing
+    pub fn flatten_into(object: &mut Object, value: JsonValue) {
+        if let JsonValue::Obj(members) = value {
+            object.extend(members);
+        }
+    }
+
+    /// the object `value` with the member `tag_key: tag` in front, content that isn't an
+    /// object is kept as the member `value` next to the tag
+    pub fn tag_object(tag_key: &str, tag: &str, value: JsonValue) -> JsonValue {
+        let mut object = Object::new();
+        object.insert(tag_key.to_string(), JsonValue::from(tag));
+        match value {
+            JsonValue::Obj(members) => object.extend(members),
+            other => {
+                object.insert(INTERNAL_VALUE.to_string(), other);
+            }
+        }
+        JsonValue::Obj(object)
+    }
+
+    /// the content of an internally tagged newtype variant with the member `tag_key: tag`
+    ///
+    /// an object that has a member `tag_key` or has `value` as its only member is kept as the
+    /// member `value` too, so [internal_content] reads back exactly what was written
+    pub fn tag_content(tag_key: &str, tag: &str, value: JsonValue) -> JsonValue {
+        match value {
+            JsonValue::Obj(members)
+                if members.contains_key(tag_key) || is_internal_value(&members) =>
+            {
+                adjacent(tag_key, tag, INTERNAL_VALUE, JsonValue::Obj(members))
+            }
+            other => tag_object(tag_key, tag, other),
+        }
+    }
+
+    /// the key non-object content of an internally tagged variant is kept under
+    const INTERNAL_VALUE: &str = "value";
+
+    /// check if `object` is the member `value` on its own, as made by [tag_content]
+    fn is_internal_value(object: &Object) -> bool {
+        object.len() == 1 && object.contains_key(INTERNAL_VALUE)
+    }
+
+    /// read the content of an internally tagged newtype variant, the reverse of [tag_content]
+    ///
+    /// the tag is left out of the content. When all that is left is the member `value` the
+    /// content is read from it, falling back to the object itself so an object with a
+    /// `value` field written by another encoder still reads
+    pub fn internal_content<T: FromJson>(
+        value: &JsonValue,
+        tag_key: &str,
+    ) -> Result<T, ConversionError> {
+        let mut rest = match value.as_object() {
+            Some(object) => object.clone(),
+            None => return T::from_json(value),
+        };
+        rest.remove(tag_key);
+        let rest = JsonValue::Obj(rest);
+        match rest.as_object().filter(|rest| is_internal_value(rest)) {
+            Some(members) => T::from_json(&members[INTERNAL_VALUE])
+                .or_else(|e| T::from_json(&rest).map_err(|_| e.at_key(INTERNAL_VALUE))),
+            None => T::from_json(&rest),
+        }
+    }
+
+    /// an object with the members `tag_key: tag` and `content_key: content`
+    pub fn adjacent(tag_key: &str, tag: &str, content_key: &str, content: JsonValue) -> JsonValue {
+        let mut object = Object::new();
+        object.insert(tag_key.to_string(), JsonValue::from(tag));
+        object.insert(content_key.to_string(), content);
+        JsonValue::Obj(object)
+    }
+
+    pub fn object<'a>(value: &'a JsonValue, expected: &str) -> Result<&'a Object, ConversionError> {
+        value.as_object().ok_or_else(|| mismatch(expected, value))
+    }
+
+    /// the elements of an array that has exactly `len` of them
+    pub fn array<'a>(
+        value: &'a JsonValue,
+        expected: &str,
+        len: usize,
+    ) -> Result<&'a [JsonValue], ConversionError> {
+        let array = value.as_array().ok_or_else(|| mismatch(expected, value))?;
+        if array.len() != len {
+            return Err(ConversionError::new(
+                expected,
+                format!("an array of length {}", array.len()),
+            ));
+        }
+        Ok(array)
+    }
+
+    pub fn null(value: &JsonValue, expected: &str) -> Result<(), ConversionError> {
+        match value {
+            JsonValue::Null => Ok(()),
+            _ => Err(mismatch(expected, value)),
+        }
+    }
+
+    pub fn element<T: FromJson>(array: &[JsonValue], index: usize) -> Result<T, ConversionError> {
+        T::from_json(&array[index]).map_err(|e| e.at_index(index))
+    }
+
+    /// read the member `key`, a missing member is read as `null` so `Option` fields can be left out
+    pub fn field<T: FromJson>(object: &Object, key: &str) -> Result<T, ConversionError> {
+        match object.get(key) {
+            Some(member) => T::from_json(member).map_err(|e| e.at_key(key)),
+            None => T::from_json(&JsonValue::Null)
+                .map_err(|e| ConversionError::new(e.expected(), "nothing").at_key(key)),
+        }
+    }
+
+    /// read the member `key`, using `default` when it is missing
+    pub fn field_or<T: FromJson>(
+        object: &Object,
+        key: &str,
+        default: impl FnOnce() -> T,
+    ) -> Result<T, ConversionError> {
+        match object.get(key) {
+            Some(member) => T::from_json(member).map_err(|e| e.at_key(key)),
+            None => Ok(default()),
+        }
+    }
+
+    /// the variant name in the member `tag_key`
+    pub fn tag<'a>(
+        object: &'a Object,
+        tag_key: &str,
+        expected: &str,
+    ) -> Result<&'a str, ConversionError> {
+        match object.get(tag_key) {
+            Some(JsonValue::String(tag)) => Ok(tag),
+            Some(other) => Err(mismatch(expected, other).at_key(tag_key)),
+            None => Err(ConversionError::new(expected, "nothing").at_key(tag_key)),
+        }
+    }
+
+    /// the variant name and content of an externally tagged enum, which is either the name as
+    /// a string or an object with the name as its only key
+    pub fn external<'a>(
+        value: &'a JsonValue,
+        expected: &str,
+    ) -> Result<(&'a str, &'a JsonValue), ConversionError> {
+        match value {
+            JsonValue::String(tag) => Ok((tag, &JsonValue::Null)),
+            JsonValue::Obj(object) if object.len() == 1 => {
+                let (tag, content) = object.iter().next().unwrap();
+                Ok((tag, content))
+            }
+            _ => Err(mismatch(expected, value)),
+        }
+    }
+
+    pub fn unknown_variant(expected: &str, tag: &str) -> ConversionError {
+        ConversionError::new(expected, format!("the unknown variant `{tag}`"))
+    }
+}
@@ -0,0 +1,292 @@
+use std::{borrow::Cow, fmt::Display, ops::Index};
+
+use crate::values::{
+    display_value, encode_value, write_io, JsonNum, JsonValue, Map, ValueIndex, ValueView, View,
+};
+
+/// a json value that borrows its strings and keys from the parsed source
+///
+/// made by [parse_borrowed](crate::json_parser::parse_borrowed), strings without escapes
+/// are [Cow::Borrowed] so reading a document only allocates for the arrays, objects and
+/// strings that had escapes in them. Use [to_owned](JsonValueRef::to_owned) to get a
+/// [JsonValue] that no longer borrows the source
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValueRef<'a> {
+    Null,
+    Bool(bool),
+    Num(JsonNum),
+    String(Cow<'a, str>),
+    Array(Vec<JsonValueRef<'a>>),
+    Obj(Map<Cow<'a, str>, JsonValueRef<'a>>),
+}
+
+impl Display for JsonValueRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        display_value(self, f)
+    }
+}
+
+impl<'a> ValueView for JsonValueRef<'a> {
+    type Key = Cow<'a, str>;
+
+    fn view(&self) -> View<'_, Cow<'a, str>, JsonValueRef<'a>> {
+        match self {
+            JsonValueRef::Null => View::Null,
+            JsonValueRef::Bool(b_val) => View::Bool(*b_val),
+            JsonValueRef::Num(num) => View::Num(num),
+            JsonValueRef::String(s_val) => View::String(s_val),
+            JsonValueRef::Array(arr) => View::Array(arr),
+            JsonValueRef::Obj(h_map) => View::Obj(h_map),
+        }
+    }
+}
+
+/// Default for `JsonValueRef` is `JsonValueRef::Null`
+impl Default for JsonValueRef<'_> {
+    /// gives the default `JsonValueRef` value
+    /// which is `JsonValueRef::Null`
+    fn default() -> Self {
+        JsonValueRef::Null
+    }
+}
+
+/// trait implementation for `JsonValueRef::Array`
+///
+/// if used on any other type or the index is out of range then return is `JsonValueRef::Null`
+impl<'a> Index<usize> for JsonValueRef<'a> {
+    type Output = JsonValueRef<'a>;
+
+    /// use to index `JsonValueRef::Array`
+    /// if used on any other type or out of range then return is `JsonValueRef::Null`
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).unwrap_or(&JsonValueRef::Null)
+    }
+}
+
+/// trait implementation for `JsonValueRef::Obj`
+///
+/// if used on any other type or the key is missing then return is `JsonValueRef::Null`
+impl<'a> Index<&str> for JsonValueRef<'a> {
+    type Output = JsonValueRef<'a>;
+
+    /// use to get value from `JsonValueRef::Obj`
+    /// if used on any other type or the key is missing then return is `JsonValueRef::Null`
+    fn index(&self, index: &str) -> &Self::Output {
+        self.get(index).unwrap_or(&JsonValueRef::Null)
+    }
+}
+
+/// typed access that never panics, the same as on [JsonValue]
+impl<'a> JsonValueRef<'a> {
+    /// check if the value is `JsonValueRef::Null`
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValueRef::Null)
+    }
+
+    /// check if the value is `JsonValueRef::Bool`
+    pub fn is_bool(&self) -> bool {
+        matches!(self, JsonValueRef::Bool(_))
+    }
+
+    /// check if the value is `JsonValueRef::Num`
+    pub fn is_number(&self) -> bool {
+        matches!(self, JsonValueRef::Num(_))
+    }
+
+    /// check if the value is `JsonValueRef::String`
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonValueRef::String(_))
+    }
+
+    /// check if the value is `JsonValueRef::Array`
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonValueRef::Array(_))
+    }
+
+    /// check if the value is `JsonValueRef::Obj`
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonValueRef::Obj(_))
+    }
+
+    /// the value of a `JsonValueRef::Bool`
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValueRef::Bool(b_val) => Some(*b_val),
+            _ => None,
+        }
+    }
+
+    /// the number in a `JsonValueRef::Num`
+    pub fn as_number(&self) -> Option<&JsonNum> {
+        match self {
+            JsonValueRef::Num(num) => Some(num),
+            _ => None,
+        }
+    }
+
+    /// a number that is an integer fitting in an `i64`, see [JsonNum::as_i64]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    /// a number that is a non negative integer fitting in a `u64`, see [JsonNum::as_u64]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    /// a number that is an integer fitting in an `i128`, see [JsonNum::as_i128]
+    pub fn as_i128(&self) -> Option<i128> {
+        self.as_number()?.as_i128()
+    }
+
+    /// a number as an `f64`, see [JsonNum::as_f64]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number()?.as_f64()
+    }
+
+    /// the text of a `JsonValueRef::String`
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValueRef::String(s_val) => Some(s_val),
+            _ => None,
+        }
+    }
+
+    /// the elements of a `JsonValueRef::Array`
+    pub fn as_array(&self) -> Option<&Vec<JsonValueRef<'a>>> {
+        match self {
+            JsonValueRef::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// the elements of a `JsonValueRef::Array` that can be changed
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValueRef<'a>>> {
+        match self {
+            JsonValueRef::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// the members of a `JsonValueRef::Obj`
+    pub fn as_object(&self) -> Option<&Map<Cow<'a, str>, JsonValueRef<'a>>> {
+        match self {
+            JsonValueRef::Obj(h_map) => Some(h_map),
+            _ => None,
+        }
+    }
+
+    /// the members of a `JsonValueRef::Obj` that can be changed
+    pub fn as_object_mut(&mut self) -> Option<&mut Map<Cow<'a, str>, JsonValueRef<'a>>> {
+        match self {
+            JsonValueRef::Obj(h_map) => Some(h_map),
+            _ => None,
+        }
+    }
+
+    /// look up an array element by `usize` or an object member by `&str`,
+    /// see [JsonValue::get]
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&JsonValueRef<'a>> {
+        index.index_into_ref(self)
+    }
+
+    /// look up an array element or object member that can be changed, see [JsonValue::get]
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut JsonValueRef<'a>> {
+        index.index_into_ref_mut(self)
+    }
+}
+
+/// any unwrap function used on the wrong type will panic, the same as on [JsonValue]
+impl JsonValueRef<'_> {
+    /// use on `JsonValueRef::Num(JsonNum::Int)` to get value.
+    /// will panic if used on other type.
+    pub fn unwrap_int(&self) -> i128 {
+        match self {
+            JsonValueRef::Num(num) => num.unwrap_int(),
+            _ => panic!("expected Int"),
+        }
+    }
+
+    /// use on `JsonValueRef::Num(JsonNum::Float)` to get value.
+    /// will panic if used on other type.
+    pub fn unwrap_float(&self) -> f64 {
+        match self {
+            JsonValueRef::Num(num) => num.unwrap_float(),
+            _ => panic!("expected Float"),
+        }
+    }
+
+    /// use on `JsonValueRef::Bool` to get value.
+    /// will panic if used on other type.
+    pub fn unwrap_bool(&self) -> bool {
+        match self {
+            JsonValueRef::Bool(b_val) => *b_val,
+            _ => panic!("expected Bool"),
+        }
+    }
+
+    /// use on `JsonValueRef::String` to get value.
+    /// will panic if used on other type.
+    pub fn unwrap_string(&self) -> &str {
+        match self {
+            JsonValueRef::String(s_val) => s_val,
+            _ => panic!("expected String"),
+        }
+    }
+
+    /// `encode` will turn a `JsonValueRef` into a `String`,
+    /// the output is the same as [JsonValue::encode] for the owned value
+    pub fn encode(&self) -> String {
+        let mut dsply_str = String::new();
+        // writing to a `String` can't fail
+        let _ = self.write_to_fmt(&mut dsply_str);
+        dsply_str
+    }
+
+    /// write the output of [encode](JsonValueRef::encode) to `writer` as it is made,
+    /// see [JsonValue::write_to]
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        write_io(writer, |out| self.write_to_fmt(out))
+    }
+
+    /// write the output of [encode](JsonValueRef::encode) to a [std::fmt::Write]
+    pub fn write_to_fmt<W: std::fmt::Write>(&self, writer: &mut W) -> std::fmt::Result {
+        encode_value(self, writer)
+    }
+
+    /// copy into a [JsonValue] that doesn't borrow from the source
+    pub fn to_owned(&self) -> JsonValue {
+        match self {
+            JsonValueRef::Null => JsonValue::Null,
+            JsonValueRef::Bool(b_val) => JsonValue::Bool(*b_val),
+            JsonValueRef::Num(num) => JsonValue::Num(num.clone()),
+            JsonValueRef::String(s_val) => JsonValue::String(s_val.to_string()),
+            JsonValueRef::Array(arr) => JsonValue::Array(arr.iter().map(Self::to_owned).collect()),
+            JsonValueRef::Obj(h_map) => JsonValue::Obj(
+                h_map
+                    .iter()
+                    .map(|(key, val)| (key.to_string(), val.to_owned()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// turn into a [JsonValue], strings that were already copied are moved instead of copied again
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            JsonValueRef::Null => JsonValue::Null,
+            JsonValueRef::Bool(b_val) => JsonValue::Bool(b_val),
+            JsonValueRef::Num(num) => JsonValue::Num(num),
+            JsonValueRef::String(s_val) => JsonValue::String(s_val.into_owned()),
+            JsonValueRef::Array(arr) => {
+                JsonValue::Array(arr.into_iter().map(Self::into_owned).collect())
+            }
+            JsonValueRef::Obj(h_map) => JsonValue::Obj(
+                h_map
+                    .into_iter()
+                    .map(|(key, val)| (key.into_owned(), val.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::value_ref::JsonValueRef;
+use crate::values::{JsonNum, JsonValue, Map, ValueView, View};
+
+/// integers are written as the smallest of `i64`, `u64`, `i128` and `u128` they fit in
+///
+/// serde has no way to hand over a number as text, so `JsonNum::Raw` is written as the integer
+/// it parses as or else the nearest `f64`. Digits past what those types hold are lost and a
+/// number too large for an `f64` is written as `NaN`, use [JsonValue::encode] to keep them
+impl Serialize for JsonNum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonNum::Int(int) => match i64::try_from(*int) {
+                Ok(int) => serializer.serialize_i64(int),
+                Err(_) => match u64::try_from(*int) {
+                    Ok(uint) => serializer.serialize_u64(uint),
+                    Err(_) => serializer.serialize_i128(*int),
+                },
+            },
+            JsonNum::UInt(uint) => serializer.serialize_u128(*uint),
+            JsonNum::Float(float) => serializer.serialize_f64(*float),
+            JsonNum::Raw(raw) => match raw.parse::<i128>() {
+                Ok(int) => JsonNum::Int(int).serialize(serializer),
+                Err(_) => match raw.parse::<u128>() {
+                    Ok(uint) => serializer.serialize_u128(uint),
+                    Err(_) => serializer.serialize_f64(self.as_f64().unwrap_or(f64::NAN)),
+                },
+            },
+        }
+    }
+}
+
+impl Serialize for JsonValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_value(self, serializer)
+    }
+}
+
+impl Serialize for JsonValueRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_value(self, serializer)
+    }
+}
+
+/// the `Serialize` of both value types
+fn serialize_value<V: ValueView + Serialize, S: Serializer>(
+    value: &V,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value.view() {
+        View::Null => serializer.serialize_unit(),
+        View::Bool(b_val) => serializer.serialize_bool(b_val),
+        View::Num(num) => num.serialize(serializer),
+        View::String(s_val) => serializer.serialize_str(s_val),
+        View::Array(arr) => {
+            let mut seq = serializer.serialize_seq(Some(arr.len()))?;
+            for element in arr {
+                seq.serialize_element(element)?;
+            }
+            seq.end()
+        }
+        View::Obj(h_map) => {
+            let mut map = serializer.serialize_map(Some(h_map.len()))?;
+            for (key, member) in h_map {
+                map.serialize_entry(key.as_ref(), member)?;
+            }
+            map.end()
+        }
+    }
+}
+
+/// call the `visit_*` method of `visitor` that fits `num`, see [JsonNum]'s `Serialize`
+pub(crate) fn visit_number<'de, V: Visitor<'de>, E: de::Error>(
+    num: JsonNum,
+    visitor: V,
+) -> Result<V::Value, E> {
+    match num {
+        JsonNum::Int(int) => match i64::try_from(int) {
+            Ok(int) => visitor.visit_i64(int),
+            Err(_) => match u64::try_from(int) {
+                Ok(uint) => visitor.visit_u64(uint),
+                Err(_) => visitor.visit_i128(int),
+            },
+        },
+        JsonNum::UInt(uint) => visitor.visit_u128(uint),
+        JsonNum::Float(float) => visitor.visit_f64(float),
+        JsonNum::Raw(ref raw) => match raw.parse::<i128>() {
+            Ok(int) => visit_number(JsonNum::Int(int), visitor),
+            Err(_) => match raw.parse::<u128>() {
+                Ok(uint) => visitor.visit_u128(uint),
+                Err(_) => visitor.visit_f64(num.as_f64().unwrap_or(f64::NAN)),
+            },
+        },
+    }
+}
+
+struct NumVisitor;
+
+impl<'de> Visitor<'de> for NumVisitor {
+    type Value = JsonNum;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<JsonNum, E> {
+        Ok(JsonNum::Int(v as i128))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<JsonNum, E> {
+        Ok(JsonNum::Int(v as i128))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<JsonNum, E> {
+        Ok(JsonNum::Int(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<JsonNum, E> {
+        Ok(match i128::try_from(v) {
+            Ok(int) => JsonNum::Int(int),
+            Err(_) => JsonNum::UInt(v),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<JsonNum, E> {
+        Ok(JsonNum::Float(v))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonNum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NumVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<JsonValue, E> {
+        NumVisitor.visit_i64(v).map(JsonValue::Num)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<JsonValue, E> {
+        NumVisitor.visit_u64(v).map(JsonValue::Num)
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<JsonValue, E> {
+        NumVisitor.visit_i128(v).map(JsonValue::Num)
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<JsonValue, E> {
+        NumVisitor.visit_u128(v).map(JsonValue::Num)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<JsonValue, E> {
+        NumVisitor.visit_f64(v).map(JsonValue::Num)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        // the size hint comes from the input so it isn't trusted with an allocation
+        let mut arr = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            arr.push(element);
+        }
+        Ok(JsonValue::Array(arr))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut h_map = Map::new();
+        while let Some((key, member)) = map.next_entry::<String, JsonValue>()? {
+            h_map.insert(key, member);
+        }
+        Ok(JsonValue::Obj(h_map))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
@@ -1,89 +1,183 @@
 use core::panic;
-use std::{collections::HashMap, fmt::Display, ops::Index};
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
 
-#[derive(Debug, PartialEq)]
+use crate::value_ref::JsonValueRef;
+
+pub use crate::map::{Entry, Map};
+
+/// write `string` as a quoted JSON string, escaping every character that
+/// can't appear in a JSON string as is
+///
+/// used by both [JsonValue::encode] and the `Display` implementation so that
+/// the output can always be read back by [json_parse](crate::json_parse)
+pub fn write_escaped_str<W: std::fmt::Write>(writer: &mut W, string: &str) -> std::fmt::Result {
+    writer.write_char('"')?;
+    let mut run_start = 0;
+    for (i, c) in string.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            '\u{0}'..='\u{1f}' => "",
+            _ => continue,
+        };
+        writer.write_str(&string[run_start..i])?;
+        if escape.is_empty() {
+            write!(writer, "\\u{:04x}", c as u32)?;
+        } else {
+            writer.write_str(escape)?;
+        }
+        run_start = i + c.len_utf8();
+    }
+    writer.write_str(&string[run_start..])?;
+    writer.write_char('"')
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum JsonNum {
     Int(i128),
     Float(f64),
+    /// an integer above `i128::MAX`, every integer that fits in an `i128` is [JsonNum::Int]
+    UInt(u128),
+    /// the number exactly as it was written in the source, made when
+    /// [ParseOptions::arbitrary_precision](crate::json_parser::ParseOptions::arbitrary_precision)
+    /// is set. It is written back out unchanged so it has to be a valid JSON number,
+    /// except through serde which only gets the nearest integer or `f64`
+    Raw(String),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum JsonValue {
     Null,
     Bool(bool),
     Num(JsonNum),
     String(String),
     Array(Vec<JsonValue>),
-    Obj(HashMap<String, JsonValue>),
+    Obj(Map<String, JsonValue>),
 }
 
 impl Display for JsonNum {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             JsonNum::Int(v) => write!(f, "{}", v),
+            JsonNum::UInt(v) => write!(f, "{}", v),
+            JsonNum::Float(v) if !v.is_finite() => write!(f, "null"),
+            JsonNum::Float(v) if v.fract() == 0.0 => write!(f, "{}.0", v),
             JsonNum::Float(v) => write!(f, "{}", v),
+            JsonNum::Raw(v) => write!(f, "{}", v),
         }
     }
 }
 
 impl Display for JsonValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        display_value(self, f)
+    }
+}
+
+/// a look at a value that doesn't care whether it owns its strings,
+/// so [JsonValue] and [JsonValueRef] can share the code that writes them out
+pub(crate) enum View<'v, K, V> {
+    Null,
+    Bool(bool),
+    Num(&'v JsonNum),
+    String(&'v str),
+    Array(&'v [V]),
+    Obj(&'v Map<K, V>),
+}
+
+/// implemented by [JsonValue] and [JsonValueRef] for the writers shared between them
+pub(crate) trait ValueView: Sized {
+    type Key: AsRef<str>;
+
+    fn view(&self) -> View<'_, Self::Key, Self>;
+}
+
+impl ValueView for JsonValue {
+    type Key = String;
+
+    fn view(&self) -> View<'_, String, JsonValue> {
         match self {
-            JsonValue::Null => write!(f, "null"),
-            JsonValue::Bool(json_val) => write!(f, "{}", json_val),
-            JsonValue::Num(json_val) => write!(f, "{}", json_val),
-            JsonValue::String(json_val) => write!(f, "\"{}\"", json_val),
-            JsonValue::Array(json_val) => {
-                let mut dsply_str = String::new();
-                let is_large = json_val.len() >= 10;
-                if !json_val.is_empty() {
-                    let last_index = json_val.len() - 1;
-                    for (i, v) in json_val.iter().enumerate() {
-                        if is_large && i % 5 == 0 {
-                            dsply_str.push_str("   ");
-                        }
-                        dsply_str.push_str(v.to_string().as_str());
-                        if i != last_index {
-                            dsply_str.push_str(", ");
-                            if is_large && (i + 1) % 5 == 0 {
-                                dsply_str.push('\n');
-                            }
+            JsonValue::Null => View::Null,
+            JsonValue::Bool(b_val) => View::Bool(*b_val),
+            JsonValue::Num(num) => View::Num(num),
+            JsonValue::String(s_val) => View::String(s_val),
+            JsonValue::Array(arr) => View::Array(arr),
+            JsonValue::Obj(h_map) => View::Obj(h_map),
+        }
+    }
+}
+
+/// the `Display` output of both value types, arrays and objects of ten or more
+/// are split over lines of five
+pub(crate) fn display_value<V: ValueView, W: std::fmt::Write>(
+    value: &V,
+    f: &mut W,
+) -> std::fmt::Result {
+    match value.view() {
+        View::Null => write!(f, "null"),
+        View::Bool(json_val) => write!(f, "{}", json_val),
+        View::Num(json_val) => write!(f, "{}", json_val),
+        View::String(json_val) => write_escaped_str(f, json_val),
+        View::Array(json_val) => {
+            let mut dsply_str = String::new();
+            let is_large = json_val.len() >= 10;
+            if !json_val.is_empty() {
+                let last_index = json_val.len() - 1;
+                for (i, v) in json_val.iter().enumerate() {
+                    if is_large && i % 5 == 0 {
+                        dsply_str.push_str("   ");
+                    }
+                    display_value(v, &mut dsply_str)?;
+                    if i != last_index {
+                        dsply_str.push_str(", ");
+                        if is_large && (i + 1) % 5 == 0 {
+                            dsply_str.push('\n');
                         }
                     }
                 }
-                write!(
-                    f,
-                    "[{nl}{}{nl}]",
-                    dsply_str,
-                    nl = if is_large { "\n" } else { "" }
-                )
             }
-            JsonValue::Obj(json_val) => {
-                let mut dsply_str = String::new();
-                let is_large = json_val.len() >= 10;
-                if !json_val.is_empty() {
-                    let last_index = json_val.len() - 1;
-
-                    for (i, (name, val)) in json_val.iter().enumerate() {
-                        if is_large && i % 5 == 0 {
-                            dsply_str.push_str("   ");
-                        }
-                        dsply_str.push_str(format!("\"{}\":{}", name, *val).as_str());
-                        if i != last_index {
-                            dsply_str.push_str(", ");
-                            if is_large && (i + 1) % 5 == 0 {
-                                dsply_str.push('\n');
-                            }
+            write!(
+                f,
+                "[{nl}{}{nl}]",
+                dsply_str,
+                nl = if is_large { "\n" } else { "" }
+            )
+        }
+        View::Obj(json_val) => {
+            let mut dsply_str = String::new();
+            let is_large = json_val.len() >= 10;
+            if !json_val.is_empty() {
+                let last_index = json_val.len() - 1;
+
+                for (i, (name, val)) in json_val.iter().enumerate() {
+                    if is_large && i % 5 == 0 {
+                        dsply_str.push_str("   ");
+                    }
+                    write_escaped_str(&mut dsply_str, name.as_ref())?;
+                    dsply_str.push(':');
+                    display_value(val, &mut dsply_str)?;
+                    if i != last_index {
+                        dsply_str.push_str(", ");
+                        if is_large && (i + 1) % 5 == 0 {
+                            dsply_str.push('\n');
                         }
                     }
                 }
-                write!(
-                    f,
-                    "{{{nl}{}{nl}}}",
-                    dsply_str,
-                    nl = if is_large { "\n" } else { "" }
-                )
             }
+            write!(
+                f,
+                "{{{nl}{}{nl}}}",
+                dsply_str,
+                nl = if is_large { "\n" } else { "" }
+            )
         }
     }
 }
@@ -99,36 +193,178 @@ impl Default for JsonValue {
 
 /// trait implementation for `JsonValue::Array`
 ///
-/// if used on any other type then return is `JsonValue::Null`
+/// if used on any other type or the index is out of range then return is `JsonValue::Null`,
+/// use [JsonValue::get] to tell those apart from an actual `null`
 impl Index<usize> for JsonValue {
     type Output = JsonValue;
 
     /// use to index `JsonValue::Array`
-    /// if used on any other type then return is `JsonValue::Null`
+    /// if used on any other type or out of range then return is `JsonValue::Null`
     fn index(&self, index: usize) -> &Self::Output {
-        match self {
-            JsonValue::Array(arr) => &arr[index],
-            _ => &JsonValue::Null,
-        }
+        self.get(index).unwrap_or(&JsonValue::Null)
     }
 }
 
 /// trait implementation for `JsonValue::Obj`
 ///
-/// if used on any other type then return is `JsonValue::Null`
+/// if used on any other type or the key is missing then return is `JsonValue::Null`,
+/// use [JsonValue::get] to tell those apart from an actual `null`
 impl Index<&str> for JsonValue {
     type Output = JsonValue;
 
     /// use to get value from `JsonValue::Obj`
-    /// if used on any other type then return is `JsonValue::Null`
+    /// if used on any other type or the key is missing then return is `JsonValue::Null`
     fn index(&self, index: &str) -> &Self::Output {
-        match self {
-            JsonValue::Obj(h_map) => h_map.index(index),
-            _ => &JsonValue::Null,
+        self.get(index).unwrap_or(&JsonValue::Null)
+    }
+}
+
+/// trait implementation for changing an element of `JsonValue::Array`
+///
+/// # Panics
+/// if used on any other type or the index is out of range
+impl IndexMut<usize> for JsonValue {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let type_name = self.type_name();
+        match self.as_array_mut() {
+            Some(arr) => {
+                let len = arr.len();
+                arr.get_mut(index).unwrap_or_else(|| {
+                    panic!("index {index} is out of range for an array of length {len}")
+                })
+            }
+            None => panic!("cannot index into {type_name} with a number"),
         }
     }
 }
 
+/// trait implementation for changing a member of `JsonValue::Obj`
+///
+/// a missing key is inserted with the value `JsonValue::Null` and `JsonValue::Null` itself
+/// becomes an empty object first, so `value["a"]["b"] = ...` builds nested objects
+///
+/// # Panics
+/// if used on any type other than `JsonValue::Obj` and `JsonValue::Null`
+impl IndexMut<&str> for JsonValue {
+    fn index_mut(&mut self, index: &str) -> &mut Self::Output {
+        self.entry(index).or_insert(JsonValue::Null)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+/// a type that can look up a value in an array or object, used by [JsonValue::get]
+/// and [JsonValueRef::get](crate::value_ref::JsonValueRef::get)
+///
+/// implemented for `usize` to index arrays and for `str` and `String` to index objects,
+/// it is sealed so it can't be implemented outside of this crate
+pub trait ValueIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue>;
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue>;
+    #[doc(hidden)]
+    fn index_into_ref<'v, 'a>(&self, value: &'v JsonValueRef<'a>) -> Option<&'v JsonValueRef<'a>>;
+    #[doc(hidden)]
+    fn index_into_ref_mut<'v, 'a>(
+        &self,
+        value: &'v mut JsonValueRef<'a>,
+    ) -> Option<&'v mut JsonValueRef<'a>>;
+    #[doc(hidden)]
+    fn remove_from(&self, value: &mut JsonValue) -> Option<JsonValue>;
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        value.as_array()?.get(*self)
+    }
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        value.as_array_mut()?.get_mut(*self)
+    }
+    fn index_into_ref<'v, 'a>(&self, value: &'v JsonValueRef<'a>) -> Option<&'v JsonValueRef<'a>> {
+        value.as_array()?.get(*self)
+    }
+    fn index_into_ref_mut<'v, 'a>(
+        &self,
+        value: &'v mut JsonValueRef<'a>,
+    ) -> Option<&'v mut JsonValueRef<'a>> {
+        value.as_array_mut()?.get_mut(*self)
+    }
+    fn remove_from(&self, value: &mut JsonValue) -> Option<JsonValue> {
+        let arr = value.as_array_mut()?;
+        (*self < arr.len()).then(|| arr.remove(*self))
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        value.as_object()?.get(self)
+    }
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        value.as_object_mut()?.get_mut(self)
+    }
+    fn index_into_ref<'v, 'a>(&self, value: &'v JsonValueRef<'a>) -> Option<&'v JsonValueRef<'a>> {
+        value.as_object()?.get(self)
+    }
+    fn index_into_ref_mut<'v, 'a>(
+        &self,
+        value: &'v mut JsonValueRef<'a>,
+    ) -> Option<&'v mut JsonValueRef<'a>> {
+        value.as_object_mut()?.get_mut(self)
+    }
+    fn remove_from(&self, value: &mut JsonValue) -> Option<JsonValue> {
+        value.as_object_mut()?.remove(self)
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        self.as_str().index_into(value)
+    }
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        self.as_str().index_into_mut(value)
+    }
+    fn index_into_ref<'v, 'a>(&self, value: &'v JsonValueRef<'a>) -> Option<&'v JsonValueRef<'a>> {
+        self.as_str().index_into_ref(value)
+    }
+    fn index_into_ref_mut<'v, 'a>(
+        &self,
+        value: &'v mut JsonValueRef<'a>,
+    ) -> Option<&'v mut JsonValueRef<'a>> {
+        self.as_str().index_into_ref_mut(value)
+    }
+    fn remove_from(&self, value: &mut JsonValue) -> Option<JsonValue> {
+        self.as_str().remove_from(value)
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        (**self).index_into(value)
+    }
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        (**self).index_into_mut(value)
+    }
+    fn index_into_ref<'v, 'a>(&self, value: &'v JsonValueRef<'a>) -> Option<&'v JsonValueRef<'a>> {
+        (**self).index_into_ref(value)
+    }
+    fn index_into_ref_mut<'v, 'a>(
+        &self,
+        value: &'v mut JsonValueRef<'a>,
+    ) -> Option<&'v mut JsonValueRef<'a>> {
+        (**self).index_into_ref_mut(value)
+    }
+    fn remove_from(&self, value: &mut JsonValue) -> Option<JsonValue> {
+        (**self).remove_from(value)
+    }
+}
+
 /// any unwrap function used on the wrong type will panic.
 /// only `JsonValue::String`, `JsonValue::Num`, `JsonValue::bool` have unwrap methods.
 /// `JsonValue::Obj`, JsonValue::Arr`, and `JsonValue::Null` cannot be unwraped
@@ -170,48 +406,367 @@ impl JsonValue {
     }
 }
 
+/// typed access that never panics, every method returns `None` or `false`
+/// when the value is a different type
+impl JsonValue {
+    /// check if the value is `JsonValue::Null`
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    /// check if the value is `JsonValue::Bool`
+    pub fn is_bool(&self) -> bool {
+        matches!(self, JsonValue::Bool(_))
+    }
+
+    /// check if the value is `JsonValue::Num`
+    pub fn is_number(&self) -> bool {
+        matches!(self, JsonValue::Num(_))
+    }
+
+    /// check if the value is `JsonValue::String`
+    pub fn is_string(&self) -> bool {
+        matches!(self, JsonValue::String(_))
+    }
+
+    /// check if the value is `JsonValue::Array`
+    pub fn is_array(&self) -> bool {
+        matches!(self, JsonValue::Array(_))
+    }
+
+    /// check if the value is `JsonValue::Obj`
+    pub fn is_object(&self) -> bool {
+        matches!(self, JsonValue::Obj(_))
+    }
+
+    /// the value of a `JsonValue::Bool`
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b_val) => Some(*b_val),
+            _ => None,
+        }
+    }
+
+    /// the number in a `JsonValue::Num`
+    pub fn as_number(&self) -> Option<&JsonNum> {
+        match self {
+            JsonValue::Num(num) => Some(num),
+            _ => None,
+        }
+    }
+
+    /// a number that is an integer fitting in an `i64`, see [JsonNum::as_i64]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    /// a number that is a non negative integer fitting in a `u64`, see [JsonNum::as_u64]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    /// a number that is an integer fitting in an `i128`, see [JsonNum::as_i128]
+    pub fn as_i128(&self) -> Option<i128> {
+        self.as_number()?.as_i128()
+    }
+
+    /// a number as an `f64`, see [JsonNum::as_f64]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number()?.as_f64()
+    }
+
+    /// the text of a `JsonValue::String`
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s_val) => Some(s_val),
+            _ => None,
+        }
+    }
+
+    /// the elements of a `JsonValue::Array`
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// the elements of a `JsonValue::Array` that can be changed
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// the members of a `JsonValue::Obj`
+    pub fn as_object(&self) -> Option<&Map<String, JsonValue>> {
+        match self {
+            JsonValue::Obj(h_map) => Some(h_map),
+            _ => None,
+        }
+    }
+
+    /// the members of a `JsonValue::Obj` that can be changed
+    pub fn as_object_mut(&mut self) -> Option<&mut Map<String, JsonValue>> {
+        match self {
+            JsonValue::Obj(h_map) => Some(h_map),
+            _ => None,
+        }
+    }
+
+    /// look up an array element by `usize` or an object member by `&str`
+    ///
+    /// `None` if the value is a different type or the element or member doesn't exist
+    /// ```
+    /// use fuz_json_parser::json_parse;
+    ///
+    /// let value = json_parse(r#"{"list": [1, 2]}"#).unwrap();
+    /// assert_eq!(value.get("list").and_then(|list| list.get(1)).and_then(|v| v.as_i64()), Some(2));
+    /// assert_eq!(value.get("list").and_then(|list| list.get(2)), None);
+    /// assert_eq!(value.get(0), None);
+    /// ```
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&JsonValue> {
+        index.index_into(self)
+    }
+
+    /// look up an array element or object member that can be changed, see [JsonValue::get]
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut JsonValue> {
+        index.index_into_mut(self)
+    }
+
+    /// look up a value by a JSON pointer (RFC 6901) like `/list/0/name`
+    ///
+    /// `~1` in a pointer stands for `/` and `~0` for `~`, the empty pointer is the value itself.
+    /// `None` if any part of the path doesn't exist
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| match value {
+            JsonValue::Obj(h_map) => h_map.get(token.as_str()),
+            JsonValue::Array(arr) => arr.get(pointer_array_index(&token)?),
+            _ => None,
+        })
+    }
+
+    /// look up a value that can be changed by a JSON pointer, see [JsonValue::pointer]
+    /// ```
+    /// use fuz_json_parser::{json_parse, values::{JsonNum, JsonValue}};
+    ///
+    /// let mut value = json_parse(r#"{"servers": [{"port": 80}]}"#).unwrap();
+    /// *value.pointer_mut("/servers/0/port").unwrap() = JsonValue::Num(JsonNum::Int(8080));
+    /// assert_eq!(value.encode(), r#"{"servers":[{"port":8080}]}"#);
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| match value {
+            JsonValue::Obj(h_map) => h_map.get_mut(token.as_str()),
+            JsonValue::Array(arr) => arr.get_mut(pointer_array_index(&token)?),
+            _ => None,
+        })
+    }
+}
+
+/// editing values in place
+impl JsonValue {
+    /// insert a member into an object and return the value it replaced,
+    /// `JsonValue::Null` becomes an empty object first
+    ///
+    /// # Panics
+    /// if used on any type other than `JsonValue::Obj` and `JsonValue::Null`
+    pub fn insert<K: Into<String>>(&mut self, key: K, value: JsonValue) -> Option<JsonValue> {
+        self.object_or_panic("insert").insert(key.into(), value)
+    }
+
+    /// the member of an object with the key `key` to insert or change it in place,
+    /// `JsonValue::Null` becomes an empty object first
+    ///
+    /// # Panics
+    /// if used on any type other than `JsonValue::Obj` and `JsonValue::Null`
+    pub fn entry<K: Into<String>>(&mut self, key: K) -> Entry<'_> {
+        self.object_or_panic("get an entry").entry(key.into())
+    }
+
+    /// add a value to the end of an array, `JsonValue::Null` becomes an empty array first
+    ///
+    /// # Panics
+    /// if used on any type other than `JsonValue::Array` and `JsonValue::Null`
+    pub fn push(&mut self, value: JsonValue) {
+        if self.is_null() {
+            *self = JsonValue::Array(Vec::new());
+        }
+        let type_name = self.type_name();
+        match self.as_array_mut() {
+            Some(arr) => arr.push(value),
+            None => panic!("cannot push onto {type_name}"),
+        }
+    }
+
+    /// remove an array element by `usize` or an object member by `&str` and return it
+    ///
+    /// elements after a removed array element move down by one.
+    /// `None` if the value is a different type or the element or member doesn't exist
+    pub fn remove<I: ValueIndex>(&mut self, index: I) -> Option<JsonValue> {
+        index.remove_from(self)
+    }
+
+    /// take the value out, leaving `JsonValue::Null` in its place
+    pub fn take(&mut self) -> JsonValue {
+        std::mem::take(self)
+    }
+
+    /// the members of an object to change, `JsonValue::Null` becomes an empty object first
+    fn object_or_panic(&mut self, action: &str) -> &mut Map<String, JsonValue> {
+        if self.is_null() {
+            *self = JsonValue::Obj(Map::new());
+        }
+        let type_name = self.type_name();
+        match self {
+            JsonValue::Obj(h_map) => h_map,
+            _ => panic!("cannot {action} on {type_name}"),
+        }
+    }
+
+    /// name of the type for panic and error messages
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "a bool",
+            JsonValue::Num(_) => "a number",
+            JsonValue::String(_) => "a string",
+            JsonValue::Array(_) => "an array",
+            JsonValue::Obj(_) => "an object",
+        }
+    }
+}
+
+/// split a JSON pointer into its unescaped reference tokens, `None` if it doesn't start with `/`
+fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = String> + '_> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return None;
+    }
+    Some(
+        pointer
+            .split('/')
+            .skip(1)
+            .map(|token| token.replace("~1", "/").replace("~0", "~")),
+    )
+}
+
+/// array index from a JSON pointer token, which has to be digits without leading zeros
+fn pointer_array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
 impl JsonValue {
     /// `encode` will turn a `JsonValue` into a `String`
     ///
     /// this is the same string that would come from the `Display` trait
     /// but with less white space
+    ///
+    /// strings and keys are escaped so the output always parses back to an equal value,
+    /// except for `NaN` and infinite floats which have no JSON representation and are written as `null`
     pub fn encode(&self) -> String {
-        match self {
-            JsonValue::Null => "null".to_string(),
-            JsonValue::Bool(json_val) => format!("{}", json_val),
-            JsonValue::Num(json_val) => format!("{}", json_val),
-            JsonValue::String(json_val) => format!("\"{}\"", json_val),
-            JsonValue::Array(json_val) => {
-                let mut dsply_str = String::new();
-                if !json_val.is_empty() {
-                    let last_index = json_val.len() - 1;
-                    for (i, v) in json_val.iter().enumerate() {
-                        dsply_str.push_str(v.encode().as_str());
-                        if i != last_index {
-                            dsply_str.push(',');
-                        }
-                    }
+        let mut dsply_str = String::new();
+        // writing to a `String` can't fail
+        let _ = self.write_to_fmt(&mut dsply_str);
+        dsply_str
+    }
+
+    /// write the output of [encode](JsonValue::encode) to `writer` as it is made,
+    /// without building the string in memory first
+    ///
+    /// many small writes are made, so wrap a file or socket in a [std::io::BufWriter]
+    /// ```
+    /// use fuz_json_parser::json;
+    ///
+    /// let mut out = Vec::new();
+    /// json!({"ids": [1, 2, 3]}).write_to(&mut out).unwrap();
+    /// assert_eq!(out, br#"{"ids":[1,2,3]}"#);
+    /// ```
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        write_io(writer, |out| self.write_to_fmt(out))
+    }
+
+    /// write the output of [encode](JsonValue::encode) to a [std::fmt::Write], like a `String`
+    /// or a [std::fmt::Formatter]
+    pub fn write_to_fmt<W: std::fmt::Write>(&self, writer: &mut W) -> std::fmt::Result {
+        encode_value(self, writer)
+    }
+}
+
+/// the output of `encode` for both value types, written to `writer` as it is made
+pub(crate) fn encode_value<V: ValueView, W: std::fmt::Write>(
+    value: &V,
+    writer: &mut W,
+) -> std::fmt::Result {
+    match value.view() {
+        View::Null => writer.write_str("null"),
+        View::Bool(json_val) => write!(writer, "{}", json_val),
+        View::Num(json_val) => write!(writer, "{}", json_val),
+        View::String(json_val) => write_escaped_str(writer, json_val),
+        View::Array(json_val) => {
+            writer.write_char('[')?;
+            for (i, v) in json_val.iter().enumerate() {
+                if i != 0 {
+                    writer.write_char(',')?;
                 }
-                format!("[{}]", dsply_str)
+                encode_value(v, writer)?;
             }
-            JsonValue::Obj(json_val) => {
-                let mut dsply_str = String::new();
-                if !json_val.is_empty() {
-                    let last_index = json_val.len() - 1;
-
-                    for (i, (name, val)) in json_val.iter().enumerate() {
-                        dsply_str.push_str(format!("\"{}\":{}", name, val.encode()).as_str());
-                        if i != last_index {
-                            dsply_str.push(',');
-                        }
-                    }
+            writer.write_char(']')
+        }
+        View::Obj(json_val) => {
+            writer.write_char('{')?;
+            for (i, (name, val)) in json_val.iter().enumerate() {
+                if i != 0 {
+                    writer.write_char(',')?;
                 }
-                format!("{{{}}}", dsply_str)
+                write_escaped_str(writer, name.as_ref())?;
+                writer.write_char(':')?;
+                encode_value(val, writer)?;
             }
+            writer.write_char('}')
         }
     }
 }
 
+/// a [std::fmt::Write] that passes everything on to a [std::io::Write]
+///
+/// `fmt::Error` can't say what went wrong so the io error is kept to be returned later
+pub(crate) struct IoWriter<W> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
+/// run `write` with `writer` behind a [std::fmt::Write], returning the io error that stopped it
+pub(crate) fn write_io<W: std::io::Write>(
+    writer: W,
+    write: impl FnOnce(&mut IoWriter<W>) -> std::fmt::Result,
+) -> std::io::Result<()> {
+    let mut out = IoWriter {
+        inner: writer,
+        error: None,
+    };
+    match write(&mut out) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(out
+            .error
+            .unwrap_or_else(|| std::io::Error::other("formatting failed"))),
+    }
+}
+
 impl JsonNum {
     /// use on `JsonNum::Int` to get value.
     /// will panic if used on other type.
@@ -219,6 +774,8 @@ impl JsonNum {
         match self {
             JsonNum::Int(inum) => *inum,
             JsonNum::Float(_) => panic!("expected Int found Float"),
+            JsonNum::UInt(_) => panic!("expected Int found UInt"),
+            JsonNum::Raw(_) => panic!("expected Int found Raw"),
         }
     }
 
@@ -228,6 +785,55 @@ impl JsonNum {
         match self {
             JsonNum::Int(_) => panic!("expected Float found Int"),
             JsonNum::Float(fnum) => *fnum,
+            JsonNum::UInt(_) => panic!("expected Float found UInt"),
+            JsonNum::Raw(_) => panic!("expected Float found Raw"),
+        }
+    }
+
+    /// the number as an `i128` if it is an integer that fits, `None` otherwise.
+    /// floats are never converted even when they have no fractional part
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            JsonNum::Int(inum) => Some(*inum),
+            JsonNum::UInt(unum) => (*unum).try_into().ok(),
+            JsonNum::Float(_) => None,
+            JsonNum::Raw(raw) => raw.parse().ok(),
+        }
+    }
+
+    /// the number as a `u128` if it is a non negative integer that fits, `None` otherwise
+    pub fn as_u128(&self) -> Option<u128> {
+        match self {
+            JsonNum::Int(inum) => (*inum).try_into().ok(),
+            JsonNum::UInt(unum) => Some(*unum),
+            JsonNum::Float(_) => None,
+            JsonNum::Raw(raw) => raw.parse().ok(),
+        }
+    }
+
+    /// the number as an `i64` if it is an integer that fits, `None` otherwise
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_i128()?.try_into().ok()
+    }
+
+    /// the number as a `u64` if it is a non negative integer that fits, `None` otherwise
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_u128()?.try_into().ok()
+    }
+
+    /// the number as a `u32` if it is a non negative integer that fits, `None` otherwise
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u128()?.try_into().ok()
+    }
+
+    /// the number as an `f64`, `None` for a raw number too large for an `f64`.
+    /// integers and raw numbers are rounded to the nearest `f64`
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonNum::Int(inum) => Some(*inum as f64),
+            JsonNum::UInt(unum) => Some(*unum as f64),
+            JsonNum::Float(fnum) => Some(*fnum),
+            JsonNum::Raw(raw) => raw.parse().ok().filter(|f: &f64| f.is_finite()),
         }
     }
 }
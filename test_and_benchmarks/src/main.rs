@@ -1,6 +1,10 @@
 #![feature(test)]
 
-use std::{env, fs, path::Path};
+use std::{
+    env, fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
 
 use args::{Args, ArgsError};
 use getopts::Occur;
@@ -23,10 +27,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let file_string = fs::read_to_string(file_path)?;
-    let parsed_data = match fuz_json_parser::json_parser::parse(file_string) {
+    let parsed_data = match fuz_json_parser::json_parser::parse(&file_string) {
         Ok(v) => v,
-        Err(e_str) => {
-            eprintln!("failed to parse json with message: {}", e_str);
+        Err(e) => {
+            eprintln!("failed to parse json\n{}", e.render_ansi(&file_string));
             return Ok(());
         }
     };
@@ -38,7 +42,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     if use_encode {
-        println!("{}\n", parsed_data.encode());
+        // streamed so large files don't have to fit in memory twice
+        let mut out = BufWriter::new(io::stdout().lock());
+        parsed_data.write_to(&mut out)?;
+        writeln!(out, "\n")?;
     }
 
     if use_pretty {